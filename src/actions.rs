@@ -0,0 +1,270 @@
+//! Compositor actions that keybindings can trigger.
+
+use std::sync::Mutex;
+
+use serde::Deserialize;
+use smithay::{
+    desktop::Window,
+    reexports::wayland_protocols::xdg::shell::server::xdg_toplevel,
+    utils::{Logical, Point, Rectangle, Size, SERIAL_COUNTER},
+};
+
+use crate::{
+    canvas::{PAN_STEP, ZOOM_STEP},
+    Clux,
+};
+
+/// Distance a window is moved by a single keyboard step, in canvas pixels.
+pub const MOVE_STEP: i32 = 64;
+/// Amount a window grows or shrinks by a single keyboard step, in canvas pixels.
+pub const RESIZE_STEP: i32 = 64;
+
+/// Name of the mode whose bindings are the top-level `[keybindings]`.
+pub const DEFAULT_MODE: &str = "default";
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Runs the binding's `command` through `sh -c`.
+    Spawn,
+    Close,
+    ToggleMaximize,
+    ToggleFullscreen,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    GrowWidth,
+    ShrinkWidth,
+    GrowHeight,
+    ShrinkHeight,
+    /// Focuses the window at the bottom of the stack, cycling through all windows.
+    FocusNext,
+    /// Sends the focused window to the bottom of the stack and focuses the one now on top.
+    FocusPrev,
+    Raise,
+    Lower,
+    ToggleOverview,
+    PanLeft,
+    PanRight,
+    PanUp,
+    PanDown,
+    ZoomIn,
+    ZoomOut,
+    ZoomReset,
+    /// Switches to the next layout listed in `[keyboard]`.
+    CycleLayout,
+    /// Switches to another binding mode, written `action = { enter_mode = "resize" }`.
+    EnterMode(String),
+    /// Goes back to the default binding mode.
+    ExitMode,
+    ReloadConfig,
+    Quit,
+}
+
+impl Action {
+    /// Action of bindings without an `action` field, which used to be picked by binding name.
+    pub fn from_binding_name(name: &str) -> Option<Self> {
+        (name == "quit").then_some(Self::Quit)
+    }
+}
+
+/// Geometry a maximized or fullscreen window goes back to, stored in its user data.
+#[derive(Default)]
+struct RestoreGeometry(Mutex<Option<Rectangle<i32, Logical>>>);
+
+impl Clux {
+    pub fn run_action(&mut self, action: Action, command: &str) {
+        match action {
+            Action::Spawn => {
+                if let Err(err) = self.spawn(command) {
+                    tracing::error!("Failed to spawn `{}`: {}", command, err);
+                }
+            }
+            Action::Close => {
+                if let Some(toplevel) = self.focused_window().as_ref().and_then(Window::toplevel) {
+                    toplevel.send_close();
+                }
+            }
+            Action::ToggleMaximize => self.toggle_focused_state(xdg_toplevel::State::Maximized),
+            Action::ToggleFullscreen => self.toggle_focused_state(xdg_toplevel::State::Fullscreen),
+            Action::MoveLeft => self.move_focused((-MOVE_STEP, 0).into()),
+            Action::MoveRight => self.move_focused((MOVE_STEP, 0).into()),
+            Action::MoveUp => self.move_focused((0, -MOVE_STEP).into()),
+            Action::MoveDown => self.move_focused((0, MOVE_STEP).into()),
+            Action::GrowWidth => self.resize_focused((RESIZE_STEP, 0).into()),
+            Action::ShrinkWidth => self.resize_focused((-RESIZE_STEP, 0).into()),
+            Action::GrowHeight => self.resize_focused((0, RESIZE_STEP).into()),
+            Action::ShrinkHeight => self.resize_focused((0, -RESIZE_STEP).into()),
+            Action::FocusNext => {
+                if let Some(window) = self.space.elements().next().cloned() {
+                    self.focus_window(&window, SERIAL_COUNTER.next_serial());
+                }
+            }
+            Action::FocusPrev => {
+                if let Some(window) = self.space.elements().last().cloned() {
+                    self.lower_window(&window);
+                }
+                if let Some(window) = self.space.elements().last().cloned() {
+                    self.focus_window(&window, SERIAL_COUNTER.next_serial());
+                }
+            }
+            Action::Raise => {
+                if let Some(window) = self.focused_window() {
+                    self.space.raise_element(&window, true);
+                }
+            }
+            Action::Lower => {
+                if let Some(window) = self.focused_window() {
+                    self.lower_window(&window);
+                }
+            }
+            Action::ToggleOverview => self.toggle_overview(),
+            Action::PanLeft => self.pan_active_output((-PAN_STEP, 0.0).into()),
+            Action::PanRight => self.pan_active_output((PAN_STEP, 0.0).into()),
+            Action::PanUp => self.pan_active_output((0.0, -PAN_STEP).into()),
+            Action::PanDown => self.pan_active_output((0.0, PAN_STEP).into()),
+            Action::ZoomIn => self.zoom_active_output(ZOOM_STEP),
+            Action::ZoomOut => self.zoom_active_output(1.0 / ZOOM_STEP),
+            Action::ZoomReset => self.reset_active_zoom(),
+            Action::CycleLayout => self.cycle_layout(),
+            Action::EnterMode(mode) => self.enter_mode(&mode),
+            Action::ExitMode => self.enter_mode(DEFAULT_MODE),
+            Action::ReloadConfig => {
+                if let Err(err) = self.reload_config() {
+                    tracing::error!("{:#}, keeping the previous config", err);
+                }
+            }
+            Action::Quit => self.loop_signal.stop(),
+        }
+    }
+
+    pub fn enter_mode(&mut self, mode: &str) {
+        if !self.bindings.has_mode(mode) {
+            tracing::warn!("No binding mode named `{}`", mode);
+            return;
+        }
+
+        tracing::debug!("Entering binding mode `{}`", mode);
+        self.mode = mode.to_string();
+    }
+
+    /// Puts `window` below every other window.
+    pub fn lower_window(&mut self, window: &Window) {
+        let others: Vec<Window> = self
+            .space
+            .elements()
+            .filter(|w| *w != window)
+            .cloned()
+            .collect();
+
+        // Raising the others in stacking order keeps their order and leaves `window` below.
+        for other in others {
+            self.space.raise_element(&other, false);
+        }
+    }
+
+    /// Clux has no taskbar to bring hidden windows back from, so minimizing a window sends it
+    /// below every other one instead, and focuses the window now on top.
+    pub fn minimize_window(&mut self, window: &Window) {
+        self.lower_window(window);
+        if let Some(top) = self.space.elements().last().cloned() {
+            self.focus_window(&top, SERIAL_COUNTER.next_serial());
+        }
+    }
+
+    fn move_focused(&mut self, delta: Point<i32, Logical>) {
+        let Some(window) = self.focused_window() else {
+            return;
+        };
+        if let Some(location) = self.space.element_location(&window) {
+            self.space.map_element(window, location + delta, false);
+        }
+    }
+
+    fn resize_focused(&mut self, delta: Size<i32, Logical>) {
+        let Some(window) = self.focused_window() else {
+            return;
+        };
+        let Some(toplevel) = window.toplevel() else {
+            return;
+        };
+
+        let size = window.geometry().size;
+        let new_size = Size::from(((size.w + delta.w).max(1), (size.h + delta.h).max(1)));
+
+        toplevel.with_pending_state(|state| {
+            state.size = Some(new_size);
+        });
+        toplevel.send_pending_configure();
+    }
+
+    /// Toggles the maximized or fullscreen `state` of the focused window.
+    pub fn toggle_focused_state(&mut self, state: xdg_toplevel::State) {
+        if let Some(window) = self.focused_window() {
+            self.toggle_window_state(&window, state);
+        }
+    }
+
+    /// Toggles the maximized or fullscreen `state` of `window`.
+    ///
+    /// Both fill the part of the canvas shown on the active output, save for the titlebar of a
+    /// maximized window; the window gets its previous geometry back once neither state is set
+    /// anymore.
+    pub fn toggle_window_state(&mut self, window: &Window, state: xdg_toplevel::State) {
+        let Some(toplevel) = window.toplevel().cloned() else {
+            return;
+        };
+        let Some(output) = self.active_output() else {
+            return;
+        };
+        let Some(target) = self.visible_canvas(&output).map(|rect| rect.to_i32_round()) else {
+            return;
+        };
+
+        window
+            .user_data()
+            .insert_if_missing_threadsafe(RestoreGeometry::default);
+        let restore = &window.user_data().get::<RestoreGeometry>().unwrap().0;
+
+        let current = toplevel.with_pending_state(|pending| pending.states.clone());
+        let was_set = current.contains(state);
+        let was_normal = !current.contains(xdg_toplevel::State::Maximized)
+            && !current.contains(xdg_toplevel::State::Fullscreen);
+
+        if was_normal {
+            *restore.lock().unwrap() = self.space.element_geometry(window);
+        }
+
+        toplevel.with_pending_state(|pending| {
+            if was_set {
+                pending.states.unset(state);
+            } else {
+                pending.states.set(state);
+            }
+        });
+
+        let still_filled = toplevel.with_pending_state(|pending| {
+            pending.states.contains(xdg_toplevel::State::Maximized)
+                || pending.states.contains(xdg_toplevel::State::Fullscreen)
+        });
+
+        let geometry = if still_filled {
+            let titlebar = self.pending_titlebar_height(window);
+            Some(Rectangle::new(
+                (target.loc.x, target.loc.y + titlebar).into(),
+                (target.size.w, (target.size.h - titlebar).max(1)).into(),
+            ))
+        } else {
+            restore.lock().unwrap().take()
+        };
+
+        if let Some(geometry) = geometry {
+            toplevel.with_pending_state(|pending| {
+                pending.size = Some(geometry.size);
+            });
+            self.space.map_element(window.clone(), geometry.loc, true);
+        }
+        toplevel.send_pending_configure();
+    }
+}
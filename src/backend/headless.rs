@@ -0,0 +1,149 @@
+// src/backend/headless.rs
+use std::time::Duration;
+
+use smithay::{
+    backend::{
+        allocator::Fourcc,
+        renderer::{damage::OutputDamageTracker, pixman::PixmanRenderer, Bind, Offscreen},
+    },
+    output::{Mode, Output, PhysicalProperties, Subpixel},
+    reexports::{
+        calloop::{
+            timer::{TimeoutAction, Timer},
+            EventLoop,
+        },
+        pixman::Image,
+        wayland_server::Display,
+    },
+    utils::{Size, Transform},
+};
+
+use crate::state::Clux;
+
+/// Number of virtual outputs, overridable with `CLUX_HEADLESS_OUTPUTS`.
+const DEFAULT_OUTPUTS: usize = 1;
+/// Size of every virtual output, overridable with `CLUX_HEADLESS_SIZE` (eg. `1280x720`).
+const DEFAULT_SIZE: (i32, i32) = (1920, 1080);
+const FRAME_INTERVAL: Duration = Duration::from_millis(16);
+
+struct HeadlessOutput {
+    output: Output,
+    buffer: Image<'static, 'static>,
+    damage_tracker: OutputDamageTracker,
+    age: usize,
+}
+
+pub fn run_headless() -> Result<(), Box<dyn std::error::Error>> {
+    let mut event_loop: EventLoop<Clux> = EventLoop::try_new()?;
+    let display: Display<Clux> = Display::new()?;
+    let mut state = Clux::new(&mut event_loop, display);
+
+    let mut renderer = PixmanRenderer::new()?;
+
+    let count = std::env::var("CLUX_HEADLESS_OUTPUTS")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(DEFAULT_OUTPUTS);
+    let size = std::env::var("CLUX_HEADLESS_SIZE")
+        .ok()
+        .and_then(|s| {
+            let (w, h) = s.split_once('x')?;
+            Some((w.parse().ok()?, h.parse().ok()?))
+        })
+        .unwrap_or(DEFAULT_SIZE);
+
+    let mut outputs = Vec::new();
+    for i in 0..count {
+        let output = create_output(&mut state, &format!("HEADLESS-{}", i + 1), size);
+        state.map_output(&output, (size.0 * i as i32, 0).into());
+
+        let buffer: Image<'static, 'static> =
+            renderer.create_buffer(Fourcc::Argb8888, Size::from((size.0, size.1)))?;
+        let damage_tracker = OutputDamageTracker::from_output(&output);
+
+        outputs.push(HeadlessOutput {
+            output,
+            buffer,
+            damage_tracker,
+            age: 0,
+        });
+    }
+
+    std::env::set_var("WAYLAND_DISPLAY", &state.socket_name);
+
+    event_loop
+        .handle()
+        .insert_source(Timer::immediate(), move |_, _, state| {
+            for headless in outputs.iter_mut() {
+                let elements = state.render_elements(&mut renderer, &headless.output);
+
+                if let Ok(mut framebuffer) = renderer.bind(&mut headless.buffer) {
+                    let result = headless.damage_tracker.render_output(
+                        &mut renderer,
+                        &mut framebuffer,
+                        headless.age,
+                        &elements,
+                        [0.1, 0.1, 0.1, 1.0],
+                    );
+                    if let Err(err) = result {
+                        tracing::warn!("Failed to render {}: {:?}", headless.output.name(), err);
+                    }
+                    // The same buffer is reused, so it always holds the previous frame.
+                    headless.age = 1;
+                }
+
+                let output = &headless.output;
+                state.space.elements().for_each(|window| {
+                    window.send_frame(
+                        output,
+                        state.start_time.elapsed(),
+                        Some(Duration::ZERO),
+                        |_, _| Some(output.clone()),
+                    )
+                });
+            }
+
+            state.refresh_space();
+            state.popups.cleanup();
+            let _ = state.display_handle.flush_clients();
+
+            TimeoutAction::ToDuration(FRAME_INTERVAL)
+        })?;
+
+    let mut running = true;
+    while running {
+        if event_loop
+            .dispatch(Some(FRAME_INTERVAL), &mut state)
+            .is_err()
+        {
+            running = false;
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates a virtual output of the given size and advertises it to clients.
+pub fn create_output(state: &mut Clux, name: &str, size: (i32, i32)) -> Output {
+    let mode = Mode {
+        size: size.into(),
+        refresh: 60_000,
+    };
+
+    let output = Output::new(
+        name.to_string(),
+        PhysicalProperties {
+            size: (0, 0).into(),
+            subpixel: Subpixel::Unknown,
+            make: "Smithay".to_string(),
+            model: "Headless".to_string(),
+            serial_number: "Unknown".to_string(),
+        },
+    );
+
+    let _global = output.create_global::<Clux>(&state.display_handle);
+    output.change_current_state(Some(mode), Some(Transform::Normal), None, Some((0, 0).into()));
+    output.set_preferred(mode);
+
+    output
+}
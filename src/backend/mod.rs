@@ -1,5 +1,7 @@
+pub mod headless;
 pub mod udev;
 pub mod winit;
 
+pub use headless::run_headless;
 pub use udev::run_udev;
 pub use winit::run_winit;
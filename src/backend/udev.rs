@@ -16,12 +16,12 @@ use smithay::{
             DrmDevice, DrmDeviceFd, DrmEvent,
         },
         egl::{EGLContext, EGLDisplay},
+        input::InputEvent,
         libinput::{LibinputInputBackend, LibinputSessionInterface},
         renderer::{gles::GlesRenderer, Color32F, ImportMem},
         session::{libseat::LibSeatSession, Session},
         udev::{primary_gpu, UdevBackend},
     },
-    desktop::{space::space_render_elements, Window},
     output::{Output, PhysicalProperties, Subpixel},
     reexports::{
         calloop::EventLoop,
@@ -52,6 +52,11 @@ pub fn run_udev() -> Result<(), Box<dyn std::error::Error>> {
     event_loop
         .handle()
         .insert_source(input_backend, move |event, _, state| {
+            match &event {
+                InputEvent::DeviceAdded { device } => state.add_input_device(device.clone()),
+                InputEvent::DeviceRemoved { device } => state.remove_input_device(device),
+                _ => {}
+            }
             state.process_input_event(event);
         })?;
 
@@ -141,13 +146,7 @@ pub fn run_udev() -> Result<(), Box<dyn std::error::Error>> {
                 let renderer = &mut *renderer_guard;
                 for (output, compositor) in compositors.iter_mut() {
                     if compositor.crtc() == crtc {
-                        let elements = space_render_elements::<GlesRenderer, Window, _>(
-                            renderer,
-                            [&state.space],
-                            output,
-                            1.0,
-                        )
-                        .expect("Output without mode");
+                        let elements = state.render_elements(renderer, output);
 
                         let _ = compositor.render_frame(
                             renderer,
@@ -167,13 +166,7 @@ pub fn run_udev() -> Result<(), Box<dyn std::error::Error>> {
         let mut renderer_guard = renderer.lock().unwrap();
         let renderer = &mut *renderer_guard;
         for (output, compositor) in compositors.iter_mut() {
-            let elements = space_render_elements::<GlesRenderer, Window, _>(
-                renderer,
-                [&state.space],
-                output,
-                1.0,
-            )
-            .expect("Output without mode");
+            let elements = state.render_elements(renderer, output);
 
             let _ = compositor.render_frame(
                 renderer,
@@ -203,7 +196,7 @@ pub fn run_udev() -> Result<(), Box<dyn std::error::Error>> {
         {
             running = false;
         }
-        state.space.refresh();
+        state.refresh_space();
         state.popups.cleanup();
         let _ = state.display_handle.flush_clients();
     }
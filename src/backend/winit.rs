@@ -3,10 +3,7 @@ use std::time::Duration;
 
 use smithay::{
     backend::{
-        renderer::{
-            damage::OutputDamageTracker, element::surface::WaylandSurfaceRenderElement,
-            gles::GlesRenderer,
-        },
+        renderer::{damage::OutputDamageTracker, gles::GlesRenderer},
         winit::{self, WinitEvent},
     },
     output::{Mode, Output, PhysicalProperties, Subpixel},
@@ -50,7 +47,7 @@ pub fn run_winit() -> Result<(), Box<dyn std::error::Error>> {
     );
     output.set_preferred(mode);
 
-    state.space.map_output(&output, (0, 0));
+    state.map_output(&output, (0, 0).into());
 
     let mut damage_tracker = OutputDamageTracker::from_output(&output);
 
@@ -73,23 +70,10 @@ pub fn run_winit() -> Result<(), Box<dyn std::error::Error>> {
                 let damage = Rectangle::from_size(size);
 
                 if let Ok((renderer, mut framebuffer)) = backend.bind() {
-                    smithay::desktop::space::render_output::<
-                        GlesRenderer,
-                        WaylandSurfaceRenderElement<GlesRenderer>,
-                        _,
-                        _,
-                    >(
-                        &output,
-                        renderer,
-                        &mut framebuffer,
-                        1.0,
-                        0,
-                        [&state.space],
-                        &[],
-                        &mut damage_tracker,
-                        [0.1, 0.1, 0.1, 1.0],
-                    )
-                    .unwrap();
+                    let elements = state.render_elements(renderer, &output);
+                    damage_tracker
+                        .render_output(renderer, &mut framebuffer, 0, &elements, [0.1, 0.1, 0.1, 1.0])
+                        .unwrap();
                 }
                 backend.submit(Some(&[damage])).unwrap();
 
@@ -102,7 +86,7 @@ pub fn run_winit() -> Result<(), Box<dyn std::error::Error>> {
                     )
                 });
 
-                state.space.refresh();
+                state.refresh_space();
                 state.popups.cleanup();
                 let _ = state.display_handle.flush_clients();
 
@@ -0,0 +1,182 @@
+//! Command-line client for the clux control socket.
+
+#[path = "../ipc/protocol.rs"]
+mod protocol;
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    os::unix::net::UnixStream,
+    process::ExitCode,
+};
+
+use protocol::{OutputInfo, Reply, Request, Response, WindowInfo, SOCKET_ENV};
+
+const USAGE: &str = "\
+USAGE: cluxctl [--json] [--socket PATH] <command>
+
+Commands:
+\twindows : List windows.
+\toutputs : List outputs.
+\tlayout : Print the active keyboard layout.
+\tmode : Print the active binding mode.
+\tfocus <id> : Focus and raise a window.
+\tmove <id> <x> <y> : Move a window on the canvas.
+\tclose <id> : Ask a window to close.
+\tspawn <command...> : Run a command through `sh -c`.
+\treload : Reload the config file.
+\tquit : Stop the compositor.";
+
+fn main() -> ExitCode {
+    let mut json = false;
+    let mut socket = std::env::var_os(SOCKET_ENV).map(Into::into);
+    let mut words = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--socket" => socket = args.next().map(Into::into),
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            _ => words.push(arg),
+        }
+    }
+
+    let request = match parse_request(&words) {
+        Ok(request) => request,
+        Err(err) => {
+            eprintln!("cluxctl: {err}");
+            eprintln!();
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let Some(socket) = socket else {
+        eprintln!("cluxctl: {SOCKET_ENV} is not set, is clux running?");
+        return ExitCode::FAILURE;
+    };
+
+    let reply = match send(&socket, &request) {
+        Ok(reply) => reply,
+        Err(err) => {
+            eprintln!("cluxctl: {}: {err}", socket.display());
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if json {
+        println!("{}", reply.trim_end());
+    }
+
+    match serde_json::from_str::<Response>(&reply) {
+        Ok(Response::Ok(reply)) => {
+            if !json {
+                print_reply(&reply);
+            }
+            ExitCode::SUCCESS
+        }
+        Ok(Response::Error(message)) => {
+            eprintln!("cluxctl: {message}");
+            ExitCode::FAILURE
+        }
+        Err(err) => {
+            eprintln!("cluxctl: invalid reply from clux: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_request(words: &[String]) -> Result<Request, String> {
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+
+    let request = match words.as_slice() {
+        ["windows"] => Request::Windows,
+        ["outputs"] => Request::Outputs,
+        ["mode"] => Request::Mode,
+        ["layout"] => Request::Layout,
+        ["focus", id] => Request::Focus { id: parse(id)? },
+        ["move", id, x, y] => Request::Move {
+            id: parse(id)?,
+            x: parse(x)?,
+            y: parse(y)?,
+        },
+        ["close", id] => Request::Close { id: parse(id)? },
+        ["spawn", command @ ..] if !command.is_empty() => Request::Spawn {
+            command: command.join(" "),
+        },
+        ["reload"] => Request::Reload,
+        ["quit"] => Request::Quit,
+        [] => return Err("missing command".into()),
+        [command, ..] => return Err(format!("invalid arguments for `{command}`")),
+    };
+
+    Ok(request)
+}
+
+fn parse<T: std::str::FromStr>(word: &str) -> Result<T, String> {
+    word.parse()
+        .map_err(|_| format!("`{word}` is not a number"))
+}
+
+fn send(socket: &std::path::Path, request: &Request) -> std::io::Result<String> {
+    let mut stream = UnixStream::connect(socket)?;
+
+    let mut line = serde_json::to_vec(request)?;
+    line.push(b'\n');
+    stream.write_all(&line)?;
+
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply)?;
+    Ok(reply)
+}
+
+fn print_reply(reply: &Reply) {
+    match reply {
+        Reply::Handled => {}
+        Reply::Outputs(outputs) => outputs.iter().for_each(print_output),
+        Reply::Windows(windows) => {
+            println!("{:>4}  {:<24} {:<20} TITLE", "ID", "APP_ID", "GEOMETRY");
+            windows.iter().rev().for_each(print_window);
+        }
+        Reply::Mode(mode) => println!("{mode}"),
+        Reply::Layout(layout) => println!("{}", layout.name),
+    }
+}
+
+fn print_output(output: &OutputInfo) {
+    println!(
+        "{} ({} {}): {}x{} at {},{} scale {} zoom {}",
+        output.name,
+        output.make,
+        output.model,
+        output.width,
+        output.height,
+        output.x,
+        output.y,
+        output.scale,
+        output.zoom,
+    );
+}
+
+fn print_window(window: &WindowInfo) {
+    let id = if window.focused {
+        format!("*{}", window.id)
+    } else {
+        window.id.to_string()
+    };
+    let geometry = format!(
+        "{}x{}+{}+{}",
+        window.width, window.height, window.x, window.y
+    );
+
+    println!(
+        "{:>4}  {:<24} {:<20} {}",
+        id,
+        window.app_id.as_deref().unwrap_or("-"),
+        geometry,
+        window.title.as_deref().unwrap_or(""),
+    );
+}
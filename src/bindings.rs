@@ -0,0 +1,392 @@
+//! Keybindings, parsed once from combos such as `ctrl+shift+return` into a lookup table.
+
+use std::{collections::HashMap, fmt};
+
+use smithay::input::keyboard::{xkb, Keysym, KeysymHandle, ModifiersState};
+
+use crate::{
+    actions::{Action, DEFAULT_MODE},
+    config::{CluxConfig, Keybinding},
+};
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct Modifiers: u8 {
+        const CTRL  = 0b0001;
+        const ALT   = 0b0010;
+        const SHIFT = 0b0100;
+        const LOGO  = 0b1000;
+    }
+}
+
+impl Modifiers {
+    pub fn from_state(state: &ModifiersState) -> Self {
+        let mut modifiers = Self::empty();
+        modifiers.set(Self::CTRL, state.ctrl);
+        modifiers.set(Self::ALT, state.alt);
+        modifiers.set(Self::SHIFT, state.shift);
+        modifiers.set(Self::LOGO, state.logo);
+        modifiers
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    Keysym(Keysym),
+    /// A raw xkb keycode, written `code:<n>`, which matches regardless of the layout.
+    Keycode(xkb::Keycode),
+    /// Scrolling, written `scroll_up`, `scroll_down`, `scroll_left` or `scroll_right`.
+    Scroll(ScrollDirection),
+    /// A touchpad gesture, see [`Gesture`].
+    Gesture(Gesture),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScrollDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl ScrollDirection {
+    const NAMES: [(&'static str, Self); 4] = [
+        ("scroll_up", Self::Up),
+        ("scroll_down", Self::Down),
+        ("scroll_left", Self::Left),
+        ("scroll_right", Self::Right),
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        Self::NAMES
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, direction)| *direction)
+    }
+
+    fn name(self) -> &'static str {
+        Self::NAMES.iter().find(|(_, d)| *d == self).unwrap().0
+    }
+}
+
+/// Touchpad gestures, written `swipe3_left` to `swipe4_down`, `pinch_in`, `pinch_out`, `hold3` or
+/// `hold4`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Gesture {
+    /// Fingers moving together, the direction being the one they move in.
+    Swipe {
+        fingers: u32,
+        direction: SwipeDirection,
+    },
+    /// Two or more fingers moving closer together (in) or apart (out).
+    Pinch { outwards: bool },
+    /// Fingers resting on the touchpad without moving.
+    Hold { fingers: u32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SwipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl SwipeDirection {
+    pub const NAMES: [(&'static str, Self); 4] = [
+        ("left", Self::Left),
+        ("right", Self::Right),
+        ("up", Self::Up),
+        ("down", Self::Down),
+    ];
+}
+
+impl Gesture {
+    /// Finger counts swipe and hold gestures can be bound for.
+    const FINGERS: [u32; 2] = [3, 4];
+
+    fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        let fingers = |count: &str| {
+            count
+                .parse()
+                .ok()
+                .filter(|fingers| Self::FINGERS.contains(fingers))
+        };
+
+        if let Some(rest) = name.strip_prefix("swipe") {
+            let (count, direction) = rest.split_once('_')?;
+            let direction = SwipeDirection::NAMES
+                .iter()
+                .find(|(n, _)| *n == direction)?
+                .1;
+            return Some(Self::Swipe {
+                fingers: fingers(count)?,
+                direction,
+            });
+        }
+        if let Some(count) = name.strip_prefix("hold") {
+            return Some(Self::Hold {
+                fingers: fingers(count)?,
+            });
+        }
+
+        match name.as_str() {
+            "pinch_in" => Some(Self::Pinch { outwards: false }),
+            "pinch_out" => Some(Self::Pinch { outwards: true }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Gesture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Swipe { fingers, direction } => {
+                let direction = SwipeDirection::NAMES
+                    .iter()
+                    .find(|(_, d)| d == direction)
+                    .unwrap()
+                    .0;
+                write!(f, "swipe{fingers}_{direction}")
+            }
+            Self::Pinch { outwards: false } => write!(f, "pinch_in"),
+            Self::Pinch { outwards: true } => write!(f, "pinch_out"),
+            Self::Hold { fingers } => write!(f, "hold{fingers}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Combo {
+    pub modifiers: Modifiers,
+    pub key: Key,
+}
+
+impl fmt::Display for Combo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, modifier) in [
+            ("ctrl", Modifiers::CTRL),
+            ("alt", Modifiers::ALT),
+            ("shift", Modifiers::SHIFT),
+            ("logo", Modifiers::LOGO),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{name}+")?;
+            }
+        }
+
+        match self.key {
+            Key::Keysym(keysym) => write!(f, "{}", xkb::keysym_get_name(keysym)),
+            Key::Keycode(keycode) => write!(f, "code:{}", keycode.raw()),
+            Key::Scroll(direction) => write!(f, "{}", direction.name()),
+            Key::Gesture(gesture) => write!(f, "{gesture}"),
+        }
+    }
+}
+
+/// Parses a `+` separated combo: any number of modifiers followed by a single key.
+///
+/// Keys are xkb keysym names matched case-insensitively, eg. `return`, `Tab` or `f1`, raw
+/// keycodes written `code:<n>`, a scroll direction such as `scroll_up`, or a touchpad gesture
+/// such as `swipe3_left`.
+pub fn parse_combo(combo: &str) -> Result<Combo, String> {
+    let parts: Vec<&str> = combo.split('+').map(str::trim).collect();
+    let (key, modifier_names) = parts.split_last().unwrap();
+
+    let mut modifiers = Modifiers::empty();
+    for name in modifier_names {
+        let modifier = parse_modifier(name).ok_or_else(|| {
+            format!("unknown modifier `{name}`, expected one of ctrl, alt, shift or logo")
+        })?;
+        if modifiers.contains(modifier) {
+            return Err(format!("modifier `{name}` is repeated"));
+        }
+        modifiers |= modifier;
+    }
+
+    if key.is_empty() {
+        return Err(format!("`{combo}` has no key"));
+    }
+    if parse_modifier(key).is_some() {
+        return Err(format!("`{combo}` ends with a modifier instead of a key"));
+    }
+
+    if let Some(code) = key.strip_prefix("code:") {
+        let code: u32 = code
+            .parse()
+            .map_err(|_| format!("`{code}` is not a keycode"))?;
+        return Ok(Combo {
+            modifiers,
+            key: Key::Keycode(code.into()),
+        });
+    }
+
+    if let Some(direction) = ScrollDirection::from_name(key) {
+        return Ok(Combo {
+            modifiers,
+            key: Key::Scroll(direction),
+        });
+    }
+
+    if let Some(gesture) = Gesture::from_name(key) {
+        return Ok(Combo {
+            modifiers,
+            key: Key::Gesture(gesture),
+        });
+    }
+
+    let keysym = xkb::keysym_from_name(key, xkb::KEYSYM_CASE_INSENSITIVE);
+    if keysym == Keysym::NoSymbol {
+        return Err(format!("unknown key `{key}`"));
+    }
+
+    Ok(Combo {
+        modifiers,
+        key: Key::Keysym(keysym),
+    })
+}
+
+pub fn parse_modifier(name: &str) -> Option<Modifiers> {
+    let modifier = match name.to_lowercase().as_str() {
+        "ctrl" | "control" => Modifiers::CTRL,
+        "alt" => Modifiers::ALT,
+        "shift" => Modifiers::SHIFT,
+        "logo" | "super" | "win" => Modifiers::LOGO,
+        _ => return None,
+    };
+
+    Some(modifier)
+}
+
+#[derive(Debug, Clone)]
+pub struct Binding {
+    pub action: Action,
+    pub command: String,
+}
+
+/// The bindings of every mode, keyed by combo.
+#[derive(Default)]
+pub struct BindingTable {
+    modes: HashMap<String, HashMap<Combo, Binding>>,
+}
+
+impl BindingTable {
+    /// Builds the table for all modes of `config`, skipping bindings whose combo does not parse.
+    pub fn new(config: &CluxConfig) -> Self {
+        let mut modes = HashMap::new();
+        modes.insert(DEFAULT_MODE.to_string(), mode_table(&config.keybindings));
+
+        for (mode, keybindings) in &config.modes {
+            if mode == DEFAULT_MODE {
+                tracing::warn!("Ignoring [modes.{}], use [keybindings] instead", mode);
+                continue;
+            }
+            modes.insert(mode.clone(), mode_table(keybindings));
+        }
+
+        Self { modes }
+    }
+
+    pub fn has_mode(&self, mode: &str) -> bool {
+        self.modes.contains_key(mode)
+    }
+
+    /// Finds the binding for a key press.
+    ///
+    /// Keycode bindings win over keysym ones. Keysyms are looked up both unshifted with every
+    /// held modifier, so `ctrl+shift+equal` works, and as typed with shift left out, so
+    /// `ctrl+plus` works on layouts where plus needs shift.
+    pub fn find(
+        &self,
+        mode: &str,
+        modifiers: &ModifiersState,
+        handle: &KeysymHandle<'_>,
+    ) -> Option<&Binding> {
+        let bindings = self.modes.get(mode)?;
+        let modifiers = Modifiers::from_state(modifiers);
+        let lookup = |modifiers, key| bindings.get(&Combo { modifiers, key });
+
+        lookup(modifiers, Key::Keycode(handle.raw_code()))
+            .or_else(|| {
+                handle
+                    .raw_syms()
+                    .iter()
+                    .find_map(|keysym| lookup(modifiers, Key::Keysym(*keysym)))
+            })
+            .or_else(|| {
+                lookup(
+                    modifiers - Modifiers::SHIFT,
+                    Key::Keysym(handle.modified_sym()),
+                )
+            })
+    }
+
+    /// Finds the binding for scrolling in `direction`.
+    pub fn find_scroll(
+        &self,
+        mode: &str,
+        modifiers: &ModifiersState,
+        direction: ScrollDirection,
+    ) -> Option<&Binding> {
+        let combo = Combo {
+            modifiers: Modifiers::from_state(modifiers),
+            key: Key::Scroll(direction),
+        };
+        self.modes.get(mode)?.get(&combo)
+    }
+
+    /// Finds the binding for a touchpad gesture.
+    pub fn find_gesture(
+        &self,
+        mode: &str,
+        modifiers: &ModifiersState,
+        gesture: Gesture,
+    ) -> Option<&Binding> {
+        let combo = Combo {
+            modifiers: Modifiers::from_state(modifiers),
+            key: Key::Gesture(gesture),
+        };
+        self.modes.get(mode)?.get(&combo)
+    }
+}
+
+fn mode_table(keybindings: &HashMap<String, Keybinding>) -> HashMap<Combo, Binding> {
+    let mut names: Vec<&String> = keybindings.keys().collect();
+    names.sort();
+
+    let mut bindings = HashMap::new();
+    for name in names {
+        let keybinding = &keybindings[name];
+        let combo = match parse_combo(&keybinding.combo) {
+            Ok(combo) => combo,
+            Err(err) => {
+                tracing::warn!("Ignoring keybinding `{}`: {}", name, err);
+                continue;
+            }
+        };
+
+        let binding = Binding {
+            action: keybinding.action(name),
+            command: keybinding.command.clone(),
+        };
+
+        // `return` has always covered the keypad enter key as well.
+        if combo.key == Key::Keysym(Keysym::Return) {
+            let keypad = Combo {
+                key: Key::Keysym(Keysym::KP_Enter),
+                ..combo
+            };
+            bindings.entry(keypad).or_insert_with(|| binding.clone());
+        }
+
+        if bindings.contains_key(&combo) {
+            tracing::warn!("Ignoring keybinding `{}`: {} is already bound", name, combo);
+            continue;
+        }
+        bindings.insert(combo, binding);
+    }
+
+    bindings
+}
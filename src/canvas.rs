@@ -0,0 +1,230 @@
+//! Outputs as movable viewports onto an unbounded canvas.
+//!
+//! The `Space` is the canvas: windows keep their canvas coordinates and every output shows the
+//! region starting at its viewport origin, scaled by its zoom level. Pointer locations are always
+//! canvas coordinates, so surfaces receive correct local coordinates at any zoom.
+
+use smithay::{
+    desktop::space::SpaceElement,
+    input::pointer::MotionEvent,
+    output::Output,
+    utils::{Logical, Point, Rectangle, SERIAL_COUNTER},
+};
+
+use crate::Clux;
+
+pub const MIN_ZOOM: f64 = 0.1;
+pub const MAX_ZOOM: f64 = 4.0;
+/// Zoom factor applied by a single zoom step.
+pub const ZOOM_STEP: f64 = 1.25;
+/// Distance panned by a single keyboard step, in output-local logical pixels.
+pub const PAN_STEP: f64 = 200.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    /// Canvas position shown at the top-left corner of the output.
+    pub origin: Point<f64, Logical>,
+    pub zoom: f64,
+}
+
+impl Viewport {
+    /// Converts an output-local position into canvas coordinates.
+    pub fn to_canvas(&self, local: Point<f64, Logical>) -> Point<f64, Logical> {
+        self.origin + local.downscale(self.zoom)
+    }
+
+    /// Converts a canvas position into output-local coordinates.
+    pub fn to_local(&self, pos: Point<f64, Logical>) -> Point<f64, Logical> {
+        (pos - self.origin).upscale(self.zoom)
+    }
+}
+
+impl Clux {
+    pub fn viewport(&self, output: &Output) -> Viewport {
+        self.viewports.get(output).copied().unwrap_or_else(|| Viewport {
+            origin: self
+                .space
+                .output_geometry(output)
+                .map(|geo| geo.loc.to_f64())
+                .unwrap_or_default(),
+            zoom: 1.0,
+        })
+    }
+
+    /// The part of the canvas currently shown on `output`.
+    pub fn visible_canvas(&self, output: &Output) -> Option<Rectangle<f64, Logical>> {
+        let size = self.space.output_geometry(output)?.size.to_f64();
+        let viewport = self.viewport(output);
+
+        Some(Rectangle::new(viewport.origin, size.downscale(viewport.zoom)))
+    }
+
+    /// Returns the output whose viewport shows the canvas position `pos`.
+    pub fn output_at(&self, pos: Point<f64, Logical>) -> Option<Output> {
+        self.space
+            .outputs()
+            .find(|output| {
+                self.visible_canvas(output)
+                    .is_some_and(|visible| visible.contains(pos))
+            })
+            .cloned()
+    }
+
+    /// Keeps a pointer moving from `from` to `to` on the canvas shown by the outputs.
+    ///
+    /// Positions inside any output are kept as they are, otherwise the pointer stops at the edge of
+    /// the output it was on.
+    pub fn clamp_to_outputs(
+        &self,
+        from: Point<f64, Logical>,
+        to: Point<f64, Logical>,
+    ) -> Point<f64, Logical> {
+        if self.output_at(to).is_some() {
+            return to;
+        }
+
+        let Some(visible) = self
+            .output_at(from)
+            .or_else(|| self.space.outputs().next().cloned())
+            .and_then(|output| self.visible_canvas(&output))
+        else {
+            return to;
+        };
+
+        // The right and bottom edges are not part of the output, stop on the last pixel.
+        let max = visible.loc + visible.size.to_point() - Point::from((1.0, 1.0));
+        Point::from((
+            to.x.clamp(visible.loc.x, max.x.max(visible.loc.x)),
+            to.y.clamp(visible.loc.y, max.y.max(visible.loc.y)),
+        ))
+    }
+
+    /// The output under the pointer, falling back to the first one.
+    pub fn active_output(&self) -> Option<Output> {
+        let pos = self.seat.get_pointer()?.current_location();
+        self.output_at(pos)
+            .or_else(|| self.space.outputs().next().cloned())
+    }
+
+    pub fn set_viewport(&mut self, output: &Output, mut viewport: Viewport) {
+        viewport.zoom = viewport.zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        self.viewports.insert(output.clone(), viewport);
+
+        // The space only knows the unzoomed size of outputs, `refresh_space` makes up for it.
+        self.space.map_output(output, viewport.origin.to_i32_round());
+    }
+
+    /// Refreshes the space, then sends windows into and out of outputs by what the outputs
+    /// show of them, whatever their zoom.
+    ///
+    /// The space takes the region an output shows to be its unzoomed size from the viewport
+    /// origin, which is only right at zoom 1.
+    pub fn refresh_space(&mut self) {
+        self.space.refresh();
+
+        for output in self.space.outputs() {
+            let (Some(mapped), Some(visible)) = (
+                self.space.output_geometry(output),
+                self.visible_canvas(output),
+            ) else {
+                continue;
+            };
+            let visible = visible.to_i32_round();
+
+            for window in self.space.elements() {
+                let Some(bbox) = self.space.element_bbox(window) else {
+                    continue;
+                };
+
+                match visible.intersection(bbox) {
+                    // Leaving twice is harmless, entering is left to the space where it agrees.
+                    None => window.output_leave(output),
+                    Some(shown) if Some(shown) == mapped.intersection(bbox) => {}
+                    Some(mut shown) => {
+                        shown.loc -= bbox.loc;
+                        window.output_enter(output, shown);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Moves the viewport of `output` by `delta` canvas units.
+    pub fn pan_output(&mut self, output: &Output, delta: Point<f64, Logical>) {
+        let mut viewport = self.viewport(output);
+        viewport.origin += delta;
+        self.set_viewport(output, viewport);
+    }
+
+    /// Changes the zoom of `output`, keeping the output-local point `anchor` in place.
+    pub fn zoom_output(&mut self, output: &Output, zoom: f64, anchor: Point<f64, Logical>) {
+        let mut viewport = self.viewport(output);
+        let fixed = viewport.to_canvas(anchor);
+
+        viewport.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        viewport.origin = fixed - anchor.downscale(viewport.zoom);
+        self.set_viewport(output, viewport);
+    }
+
+    /// Pans the active output by `delta` output-local pixels.
+    pub fn pan_active_output(&mut self, delta: Point<f64, Logical>) {
+        let Some(output) = self.active_output() else {
+            return;
+        };
+        let before = self.viewport(&output);
+
+        self.pan_output(&output, delta.downscale(before.zoom));
+        self.follow_viewport(&output, before);
+    }
+
+    /// Multiplies the zoom of the active output by `factor`, centered on the pointer.
+    pub fn zoom_active_output(&mut self, factor: f64) {
+        let Some(output) = self.active_output() else {
+            return;
+        };
+        let before = self.viewport(&output);
+        let pointer = self.seat.get_pointer().unwrap().current_location();
+
+        self.zoom_output(&output, before.zoom * factor, before.to_local(pointer));
+        self.follow_viewport(&output, before);
+    }
+
+    pub fn reset_active_zoom(&mut self) {
+        if let Some(output) = self.active_output() {
+            let zoom = self.viewport(&output).zoom;
+            self.zoom_active_output(1.0 / zoom);
+        }
+    }
+
+    /// Keeps the pointer at the same spot on the screen after the viewport of `output` changed.
+    fn follow_viewport(&mut self, output: &Output, before: Viewport) {
+        let pointer = self.seat.get_pointer().unwrap();
+        let pos = pointer.current_location();
+
+        // Only a pointer that was on this output moves along with its viewport.
+        let Some(size) = self.space.output_geometry(output).map(|geo| geo.size.to_f64()) else {
+            return;
+        };
+        if !Rectangle::new(before.origin, size.downscale(before.zoom)).contains(pos) {
+            return;
+        }
+
+        let location = self.viewport(output).to_canvas(before.to_local(pos));
+        let under = if self.overview.is_some() {
+            None
+        } else {
+            self.surface_under(location)
+        };
+
+        pointer.motion(
+            self,
+            under,
+            &MotionEvent {
+                location,
+                serial: SERIAL_COUNTER.next_serial(),
+                time: self.start_time.elapsed().as_millis() as u32,
+            },
+        );
+        pointer.frame(self);
+    }
+}
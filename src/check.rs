@@ -0,0 +1,417 @@
+//! `clux --check-config`: validates a config file without starting the compositor.
+
+use std::{collections::HashMap, ops::Range, path::Path};
+
+use serde::Deserialize;
+use toml::Spanned;
+
+use crate::{
+    actions::{Action, DEFAULT_MODE},
+    bindings::parse_combo,
+    config::{CluxConfig, Keybinding, KeyboardConfig},
+    keyboard::{compile_layout, read_keymap_file},
+};
+
+/// Output name prefixes used by the backends: DRM connector types, plus the nested and headless
+/// outputs.
+const OUTPUT_PREFIXES: &[&str] = &[
+    "Unknown",
+    "VGA",
+    "DVI-I",
+    "DVI-D",
+    "DVI-A",
+    "Composite",
+    "SVIDEO",
+    "LVDS",
+    "Component",
+    "DIN",
+    "DP",
+    "HDMI-A",
+    "HDMI-B",
+    "TV",
+    "eDP",
+    "Virtual",
+    "DSI",
+    "DPI",
+    "Writeback",
+    "SPI",
+    "USB",
+    "HEADLESS",
+];
+
+const MAX_SCALE: f32 = 10.0;
+/// libinput takes acceleration speeds from -1.0 to 1.0.
+const MAX_ACCEL_SPEED: f64 = 1.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// Byte range in the config source, if the problem can be pinned down.
+    pub span: Option<Range<usize>>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(span: Option<Range<usize>>, message: impl Into<String>) -> Self {
+        Self {
+            span,
+            message: message.into(),
+        }
+    }
+
+    /// One-based line and column of the start of the span in `source`.
+    pub fn location(&self, source: &str) -> Option<(usize, usize)> {
+        let start = self.span.as_ref()?.start.min(source.len());
+        let before = &source[..start];
+        let line = before.matches('\n').count() + 1;
+        let column = before[before.rfind('\n').map_or(0, |i| i + 1)..]
+            .chars()
+            .count()
+            + 1;
+
+        Some((line, column))
+    }
+}
+
+/// Checks the config at `path`, printing a diagnostic per problem. Returns whether it is valid.
+pub fn check_config(path: &Path) -> bool {
+    let source = match std::fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(err) => {
+            eprintln!("{}: error: {}", path.display(), err);
+            return false;
+        }
+    };
+
+    let diagnostics = check_source(&source);
+    for diagnostic in &diagnostics {
+        match diagnostic.location(&source) {
+            Some((line, column)) => eprintln!(
+                "{}:{}:{}: error: {}",
+                path.display(),
+                line,
+                column,
+                diagnostic.message
+            ),
+            None => eprintln!("{}: error: {}", path.display(), diagnostic.message),
+        }
+    }
+
+    if diagnostics.is_empty() {
+        println!("{}: ok", path.display());
+    }
+
+    diagnostics.is_empty()
+}
+
+/// Spans of the values that get validated beyond what parsing a [`CluxConfig`] checks.
+#[derive(Deserialize, Default)]
+struct Spans {
+    #[serde(default)]
+    keyboard: KeyboardSpans,
+    #[serde(default)]
+    keybindings: HashMap<String, BindingSpans>,
+    #[serde(default)]
+    modes: HashMap<String, HashMap<String, BindingSpans>>,
+    #[serde(default)]
+    outputs: Vec<OutputSpans>,
+    #[serde(default)]
+    pointer: PointerSpans,
+    #[serde(default)]
+    input: InputSpans,
+}
+
+#[derive(Deserialize, Default)]
+struct KeyboardSpans {
+    layout: Option<Spanned<toml::Value>>,
+    keymap_file: Option<Spanned<toml::Value>>,
+    #[serde(default)]
+    devices: Vec<Spanned<KeyboardDeviceSpans>>,
+}
+
+#[derive(Deserialize)]
+struct KeyboardDeviceSpans {
+    layout: Option<Spanned<toml::Value>>,
+    keymap_file: Option<Spanned<toml::Value>>,
+}
+
+#[derive(Deserialize, Default)]
+struct PointerSpans {
+    drag_modifier: Option<Spanned<toml::Value>>,
+}
+
+#[derive(Deserialize, Default)]
+struct InputSpans {
+    accel_speed: Option<Spanned<toml::Value>>,
+    #[serde(default)]
+    devices: Vec<Spanned<InputDeviceSpans>>,
+}
+
+#[derive(Deserialize)]
+struct InputDeviceSpans {
+    output: Option<Spanned<toml::Value>>,
+    region: Option<Spanned<toml::Value>>,
+    accel_speed: Option<Spanned<toml::Value>>,
+}
+
+#[derive(Deserialize)]
+struct BindingSpans {
+    combo: Option<Spanned<toml::Value>>,
+    action: Option<Spanned<toml::Value>>,
+    command: Option<Spanned<toml::Value>>,
+}
+
+#[derive(Deserialize)]
+struct OutputSpans {
+    name: Option<Spanned<toml::Value>>,
+    scale: Option<Spanned<toml::Value>>,
+}
+
+fn span<T>(value: Option<&Spanned<T>>) -> Option<Range<usize>> {
+    value.map(Spanned::span)
+}
+
+/// Returns every problem found in the config `source`, in the order they appear.
+pub fn check_source(source: &str) -> Vec<Diagnostic> {
+    let config: CluxConfig = match toml::from_str(source) {
+        Ok(config) => config,
+        Err(err) => return vec![Diagnostic::new(err.span(), err.message().trim_end())],
+    };
+    let spans: Spans = toml::from_str(source).unwrap_or_default();
+
+    let mut diagnostics = Vec::new();
+
+    check_keymap(
+        &config.keyboard,
+        span(spans.keyboard.layout.as_ref()),
+        span(spans.keyboard.keymap_file.as_ref()),
+        &mut diagnostics,
+    );
+    for idx in 0..config.keyboard.devices.len() {
+        let device_spans = spans.keyboard.devices.get(idx);
+        // An entry that leaves the layout to xkb's default is pointed at as a whole.
+        let entry_span = device_spans.map(Spanned::span);
+        check_keymap(
+            &config.keyboard.keymap(Some(idx)),
+            device_spans
+                .and_then(|d| span(d.get_ref().layout.as_ref()))
+                .or(entry_span.clone()),
+            device_spans
+                .and_then(|d| span(d.get_ref().keymap_file.as_ref()))
+                .or(entry_span),
+            &mut diagnostics,
+        );
+    }
+
+    if config.pointer.drag_modifier().is_none() {
+        diagnostics.push(Diagnostic::new(
+            span(spans.pointer.drag_modifier.as_ref()),
+            format!(
+                "unknown drag modifier `{}`, expected one of ctrl, alt, shift or logo",
+                config.pointer.drag_modifier
+            ),
+        ));
+    }
+
+    let no_spans = HashMap::new();
+    check_bindings(
+        &config,
+        &config.keybindings,
+        &spans.keybindings,
+        &mut diagnostics,
+    );
+    for (mode, keybindings) in &config.modes {
+        let mode_spans = spans.modes.get(mode).unwrap_or(&no_spans);
+        if mode == DEFAULT_MODE {
+            let first = keybindings
+                .keys()
+                .min()
+                .and_then(|name| mode_spans.get(name));
+            diagnostics.push(Diagnostic::new(
+                first.and_then(|b| span(b.combo.as_ref())),
+                "[modes.default] is never used, the default mode's bindings go in [keybindings]",
+            ));
+        }
+        check_bindings(&config, keybindings, mode_spans, &mut diagnostics);
+    }
+
+    let input = &config.input;
+    check_accel_speed(
+        input.accel_speed,
+        span(spans.input.accel_speed.as_ref()),
+        &mut diagnostics,
+    );
+    for (idx, device) in input.devices.iter().enumerate() {
+        let device_spans = spans.input.devices.get(idx);
+        if device.name.is_none() && device.kind.is_none() {
+            diagnostics.push(Diagnostic::new(
+                device_spans.map(Spanned::span),
+                "[[input.devices]] entry has neither a `name` nor a `type`, put settings for every \
+                 device directly in [input]",
+            ));
+        }
+        if let Some(output) = device.output.as_ref() {
+            if !is_valid_output_name(output) {
+                diagnostics.push(Diagnostic::new(
+                    device_spans.and_then(|d| span(d.get_ref().output.as_ref())),
+                    format!("input device mapped to unknown output `{output}`"),
+                ));
+            }
+        }
+        if let Some([_, _, width, height]) = device.region {
+            if width <= 0 || height <= 0 {
+                diagnostics.push(Diagnostic::new(
+                    device_spans.and_then(|d| span(d.get_ref().region.as_ref())),
+                    format!("input device region is {width}x{height}, expected a positive size"),
+                ));
+            }
+        }
+        check_accel_speed(
+            device.accel_speed,
+            device_spans.and_then(|d| span(d.get_ref().accel_speed.as_ref())),
+            &mut diagnostics,
+        );
+    }
+
+    let mut seen_outputs = HashMap::new();
+    for (idx, output) in config.outputs.iter().enumerate() {
+        let output_spans = spans.outputs.get(idx);
+        let name_span = output_spans.and_then(|o| span(o.name.as_ref()));
+
+        if !is_valid_output_name(&output.name) {
+            diagnostics.push(Diagnostic::new(
+                name_span.clone(),
+                format!(
+                    "unknown output name `{}`, expected a connector such as `HDMI-A-1` or `DP-2`",
+                    output.name
+                ),
+            ));
+        }
+        if seen_outputs.insert(output.name.as_str(), idx).is_some() {
+            diagnostics.push(Diagnostic::new(
+                name_span,
+                format!("output `{}` is configured more than once", output.name),
+            ));
+        }
+
+        if !(output.scale > 0.0 && output.scale <= MAX_SCALE) {
+            diagnostics.push(Diagnostic::new(
+                output_spans.and_then(|o| span(o.scale.as_ref())),
+                format!(
+                    "scale {} of `{}` is out of range, expected more than 0 and at most {MAX_SCALE}",
+                    output.scale, output.name
+                ),
+            ));
+        }
+    }
+
+    diagnostics.sort_by_key(|d| d.span.as_ref().map_or(usize::MAX, |span| span.start));
+    diagnostics
+}
+
+/// Checks the bindings of a single mode.
+fn check_bindings(
+    config: &CluxConfig,
+    keybindings: &HashMap<String, Keybinding>,
+    spans: &HashMap<String, BindingSpans>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    // Sorted by name so that conflicts are reported the same way on every run.
+    let mut names: Vec<&String> = keybindings.keys().collect();
+    names.sort();
+
+    let mut bound = HashMap::new();
+    for name in names {
+        let binding = &keybindings[name];
+        let binding_spans = spans.get(name);
+        let combo_span = binding_spans.and_then(|b| span(b.combo.as_ref()));
+
+        let action = binding.action(name);
+        let is_spawn = action == Action::Spawn;
+        if is_spawn && binding.command.trim().is_empty() {
+            diagnostics.push(Diagnostic::new(
+                combo_span.clone(),
+                format!("`{name}` has neither an `action` nor a `command`"),
+            ));
+        }
+        if !is_spawn && !binding.command.is_empty() {
+            diagnostics.push(Diagnostic::new(
+                binding_spans.and_then(|b| span(b.command.as_ref())),
+                format!("`{name}` runs an action, so its `command` is never used"),
+            ));
+        }
+        if let Action::EnterMode(mode) = &action {
+            if mode != DEFAULT_MODE && !config.modes.contains_key(mode) {
+                diagnostics.push(Diagnostic::new(
+                    binding_spans.and_then(|b| span(b.action.as_ref())),
+                    format!("`{name}` enters mode `{mode}`, but there is no [modes.{mode}]"),
+                ));
+            }
+        }
+
+        match parse_combo(&binding.combo) {
+            Ok(combo) => {
+                if let Some(other) = bound.insert(combo, name) {
+                    diagnostics.push(Diagnostic::new(
+                        combo_span,
+                        format!("`{name}` binds {combo}, which is already bound by `{other}`"),
+                    ));
+                }
+            }
+            Err(message) => {
+                diagnostics.push(Diagnostic::new(
+                    combo_span,
+                    format!("invalid combo for `{name}`: {message}"),
+                ));
+            }
+        }
+    }
+}
+
+fn check_accel_speed(
+    speed: Option<f64>,
+    span: Option<Range<usize>>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let Some(speed) = speed else {
+        return;
+    };
+
+    if !(-MAX_ACCEL_SPEED..=MAX_ACCEL_SPEED).contains(&speed) {
+        diagnostics.push(Diagnostic::new(
+            span,
+            format!(
+                "accel_speed {speed} is out of range, expected -{MAX_ACCEL_SPEED} to {MAX_ACCEL_SPEED}"
+            ),
+        ));
+    }
+}
+
+/// Makes sure xkb can compile the keymap `keyboard` describes, from its keymap file if it has one.
+fn check_keymap(
+    keyboard: &KeyboardConfig,
+    layout: Option<Range<usize>>,
+    keymap_file: Option<Range<usize>>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if let Some(path) = &keyboard.keymap_file {
+        if let Err(err) = read_keymap_file(path) {
+            diagnostics.push(Diagnostic::new(keymap_file, format!("{err:#}")));
+        }
+    } else if let Err(err) = compile_layout(keyboard) {
+        diagnostics.push(Diagnostic::new(layout, format!("{err:#}")));
+    }
+}
+
+fn is_valid_output_name(name: &str) -> bool {
+    if name == "winit" {
+        return true;
+    }
+
+    let Some((prefix, index)) = name.rsplit_once('-') else {
+        return false;
+    };
+
+    OUTPUT_PREFIXES.contains(&prefix)
+        && !index.is_empty()
+        && index.bytes().all(|b| b.is_ascii_digit())
+}
@@ -1,17 +1,39 @@
-use serde::Deserialize;
+use anyhow::Context;
+use serde::{Deserialize, Deserializer};
+use smithay::input::keyboard::XkbConfig;
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    actions::Action,
+    bindings::{parse_modifier, Modifiers},
+};
 
 #[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
 pub struct CluxConfig {
+    #[serde(default)]
     pub keyboard: KeyboardConfig,
+    #[serde(default)]
     pub keybindings: HashMap<String, Keybinding>,
+    /// Extra sets of bindings, only active while their mode is, eg. `[modes.resize.grow]`.
+    #[serde(default)]
+    pub modes: HashMap<String, HashMap<String, Keybinding>>,
     #[serde(default)]
     pub outputs: Vec<OutputConfig>,
+    #[serde(default)]
+    pub pointer: PointerConfig,
+    #[serde(default)]
+    pub cursor: CursorConfig,
+    #[serde(default)]
+    pub input: InputConfig,
+    #[serde(default)]
+    pub decorations: DecorationsConfig,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
 pub struct OutputConfig {
     pub name: String,
     pub pos: (i32, i32),
@@ -24,15 +46,306 @@ fn default_scale() -> f32 {
 }
 
 #[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PointerConfig {
+    /// Modifier that turns left-drags into moves and right-drags into resizes.
+    #[serde(default = "default_drag_modifier")]
+    pub drag_modifier: String,
+}
+
+fn default_drag_modifier() -> String {
+    "logo".to_string()
+}
+
+/// libinput settings for every device, eg. `tap = true`, followed by `[[input.devices]]` entries
+/// overriding them for the devices they match.
+#[derive(Deserialize, Default, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct InputConfig {
+    pub tap: Option<bool>,
+    pub natural_scroll: Option<bool>,
+    pub accel_profile: Option<AccelProfile>,
+    /// From -1.0 (slowest) to 1.0 (fastest).
+    pub accel_speed: Option<f64>,
+    pub left_handed: Option<bool>,
+    pub scroll_method: Option<ScrollMethod>,
+    pub disable_while_typing: Option<bool>,
+    pub middle_emulation: Option<bool>,
+    #[serde(default)]
+    pub devices: Vec<InputDeviceConfig>,
+}
+
+/// Settings for the devices matching both `name` and `type`, when given.
+#[derive(Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct InputDeviceConfig {
+    /// Device name as libinput reports it, see `libinput list-devices`.
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: Option<DeviceKind>,
+    /// Output that absolute positions map to, eg. the display a touchscreen is part of.
+    pub output: Option<String>,
+    /// Part of that output absolute positions map to instead of all of it, written
+    /// `[x, y, width, height]` in logical pixels from the output's top-left corner.
+    pub region: Option<[i32; 4]>,
+    pub tap: Option<bool>,
+    pub natural_scroll: Option<bool>,
+    pub accel_profile: Option<AccelProfile>,
+    pub accel_speed: Option<f64>,
+    pub left_handed: Option<bool>,
+    pub scroll_method: Option<ScrollMethod>,
+    pub disable_while_typing: Option<bool>,
+    pub middle_emulation: Option<bool>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceKind {
+    Keyboard,
+    Pointer,
+    Touchpad,
+    Touch,
+    Tablet,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AccelProfile {
+    Flat,
+    Adaptive,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScrollMethod {
+    NoScroll,
+    TwoFinger,
+    Edge,
+    OnButtonDown,
+}
+
+/// The settings of a device once the global ones and every matching entry are combined.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DeviceSettings {
+    pub tap: Option<bool>,
+    pub natural_scroll: Option<bool>,
+    pub accel_profile: Option<AccelProfile>,
+    pub accel_speed: Option<f64>,
+    pub left_handed: Option<bool>,
+    pub scroll_method: Option<ScrollMethod>,
+    pub disable_while_typing: Option<bool>,
+    pub middle_emulation: Option<bool>,
+}
+
+/// Cursor drawn when the client under the pointer has not set one, also passed on to spawned
+/// clients as `XCURSOR_THEME` and `XCURSOR_SIZE`.
+#[derive(Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CursorConfig {
+    #[serde(default = "default_cursor_theme")]
+    pub theme: String,
+    /// Size in logical pixels.
+    #[serde(default = "default_cursor_size")]
+    pub size: u32,
+}
+
+fn default_cursor_theme() -> String {
+    std::env::var("XCURSOR_THEME").unwrap_or_else(|_| "default".to_string())
+}
+
+fn default_cursor_size() -> u32 {
+    std::env::var("XCURSOR_SIZE")
+        .ok()
+        .and_then(|size| size.parse().ok())
+        .unwrap_or(24)
+}
+
+/// Titlebar and border drawn around windows whose clients leave their decorations to Clux.
+#[derive(Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DecorationsConfig {
+    /// Decorations of clients that leave the choice to the compositor. Clients asking for one
+    /// or the other get what they asked for.
+    #[serde(default)]
+    pub mode: DecorationMode,
+    /// Height of the titlebar and its buttons, in logical pixels.
+    #[serde(default = "default_titlebar_height")]
+    pub titlebar_height: u32,
+    #[serde(default = "default_border_width")]
+    pub border_width: u32,
+    /// Pango font description of the title, eg. `sans bold 12`, its size in logical pixels.
+    #[serde(default = "default_title_font")]
+    pub font: String,
+    /// Colors are written `#rrggbb` or `#rrggbbaa`.
+    #[serde(default = "default_focused_color")]
+    pub focused_color: Color,
+    #[serde(default = "default_unfocused_color")]
+    pub unfocused_color: Color,
+    #[serde(default = "default_text_color")]
+    pub text_color: Color,
+    #[serde(default = "default_close_color")]
+    pub close_color: Color,
+    #[serde(default = "default_maximize_color")]
+    pub maximize_color: Color,
+    #[serde(default = "default_minimize_color")]
+    pub minimize_color: Color,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DecorationMode {
+    /// Drawn by Clux.
+    #[default]
+    Server,
+    /// Drawn by the client itself, if at all.
+    Client,
+}
+
+/// Non-premultiplied RGBA, each component from 0 to 1.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(try_from = "String")]
+pub struct Color(pub [f32; 4]);
+
+impl TryFrom<String> for Color {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let invalid = || format!("invalid color `{value}`, expected #rrggbb or #rrggbbaa");
+
+        let hex = value.strip_prefix('#').ok_or_else(invalid)?;
+        if !matches!(hex.len(), 6 | 8) || !hex.is_ascii() {
+            return Err(invalid());
+        }
+        let mut color = [1.0; 4];
+        for (component, idx) in color.iter_mut().zip((0..hex.len()).step_by(2)) {
+            let byte = u8::from_str_radix(&hex[idx..idx + 2], 16).map_err(|_| invalid())?;
+            *component = byte as f32 / 255.0;
+        }
+
+        Ok(Self(color))
+    }
+}
+
+fn default_titlebar_height() -> u32 {
+    24
+}
+
+fn default_border_width() -> u32 {
+    2
+}
+
+fn default_title_font() -> String {
+    "sans 12".to_string()
+}
+
+fn default_focused_color() -> Color {
+    Color([0.2, 0.33, 0.5, 1.0])
+}
+
+fn default_unfocused_color() -> Color {
+    Color([0.2, 0.2, 0.2, 1.0])
+}
+
+fn default_text_color() -> Color {
+    Color([1.0, 1.0, 1.0, 1.0])
+}
+
+fn default_close_color() -> Color {
+    Color([0.85, 0.33, 0.31, 1.0])
+}
+
+fn default_maximize_color() -> Color {
+    Color([0.36, 0.72, 0.36, 1.0])
+}
+
+fn default_minimize_color() -> Color {
+    Color([0.94, 0.68, 0.31, 1.0])
+}
+
+/// Keymap and key repeat. Several layouts can be given as a list, eg. `layout = ["us", "de"]`,
+/// with their variants in the same order.
+#[derive(Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
 pub struct KeyboardConfig {
+    #[serde(default, deserialize_with = "comma_list")]
     pub layout: String,
+    #[serde(default, deserialize_with = "comma_list")]
     pub variant: String,
+    /// xkb options, eg. `grp:alt_shift_toggle` to switch layouts with alt+shift.
     pub options: Option<String>,
+    /// Full `.xkb` keymap used instead of `layout`, `variant` and `options`, eg. one written by
+    /// `xkbcomp`. A leading `~/` stands for the home directory.
+    pub keymap_file: Option<PathBuf>,
+    /// Milliseconds a key is held before it starts repeating.
+    #[serde(default = "default_repeat_delay")]
+    pub repeat_delay: u32,
+    /// Repeats per second, 0 to turn key repeat off.
+    #[serde(default = "default_repeat_rate")]
+    pub repeat_rate: u32,
+    #[serde(default)]
+    pub layout_tracking: LayoutTracking,
+    /// Keymaps of particular keyboards, used while typing on them instead of the one above.
+    #[serde(default)]
+    pub devices: Vec<KeyboardDeviceConfig>,
+}
+
+/// Keymap of the keyboard called `name`, eg. an external one with a different layout than the
+/// laptop's. Key repeat stays as set in `[keyboard]`.
+#[derive(Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct KeyboardDeviceConfig {
+    /// Device name as libinput reports it, see `libinput list-devices`.
+    pub name: String,
+    #[serde(default, deserialize_with = "comma_list")]
+    pub layout: String,
+    #[serde(default, deserialize_with = "comma_list")]
+    pub variant: String,
+    pub options: Option<String>,
+    pub keymap_file: Option<PathBuf>,
+}
+
+/// What the active layout belongs to when there are several.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LayoutTracking {
+    /// One layout for everything, until it is switched again.
+    #[default]
+    Global,
+    /// Each window keeps the layout it had when it lost the keyboard focus, and gets it back
+    /// when focused again. Windows start out with the first layout.
+    Window,
+}
+
+fn default_repeat_delay() -> u32 {
+    200
+}
+
+fn default_repeat_rate() -> u32 {
+    25
+}
+
+/// Reads either a single string or a list of them, joined with commas as xkb expects.
+fn comma_list<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(value) => value,
+        OneOrMany::Many(values) => values.join(","),
+    })
 }
 
 #[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Keybinding {
     pub combo: String,
+    #[serde(default)]
+    pub action: Option<Action>,
+    #[serde(default)]
     pub command: String,
 }
 
@@ -42,26 +355,191 @@ impl Default for KeyboardConfig {
             layout: "us".to_string(),
             variant: "".to_string(),
             options: None,
+            keymap_file: None,
+            repeat_delay: default_repeat_delay(),
+            repeat_rate: default_repeat_rate(),
+            layout_tracking: LayoutTracking::default(),
+            devices: Vec::new(),
         }
     }
 }
 
-pub fn load_config() -> CluxConfig {
+impl Default for PointerConfig {
+    fn default() -> Self {
+        Self {
+            drag_modifier: default_drag_modifier(),
+        }
+    }
+}
+
+impl Default for CursorConfig {
+    fn default() -> Self {
+        Self {
+            theme: default_cursor_theme(),
+            size: default_cursor_size(),
+        }
+    }
+}
+
+impl Default for DecorationsConfig {
+    fn default() -> Self {
+        Self {
+            mode: DecorationMode::default(),
+            titlebar_height: default_titlebar_height(),
+            border_width: default_border_width(),
+            font: default_title_font(),
+            focused_color: default_focused_color(),
+            unfocused_color: default_unfocused_color(),
+            text_color: default_text_color(),
+            close_color: default_close_color(),
+            maximize_color: default_maximize_color(),
+            minimize_color: default_minimize_color(),
+        }
+    }
+}
+
+impl InputConfig {
+    /// Settings for a device called `name` of the given kinds, later entries winning over
+    /// earlier ones and over the global settings.
+    pub fn device_settings(&self, name: &str, kinds: &[DeviceKind]) -> DeviceSettings {
+        let mut settings = DeviceSettings {
+            tap: self.tap,
+            natural_scroll: self.natural_scroll,
+            accel_profile: self.accel_profile,
+            accel_speed: self.accel_speed,
+            left_handed: self.left_handed,
+            scroll_method: self.scroll_method,
+            disable_while_typing: self.disable_while_typing,
+            middle_emulation: self.middle_emulation,
+        };
+
+        for device in self.devices.iter().filter(|d| d.matches(name, kinds)) {
+            settings = DeviceSettings {
+                tap: device.tap.or(settings.tap),
+                natural_scroll: device.natural_scroll.or(settings.natural_scroll),
+                accel_profile: device.accel_profile.or(settings.accel_profile),
+                accel_speed: device.accel_speed.or(settings.accel_speed),
+                left_handed: device.left_handed.or(settings.left_handed),
+                scroll_method: device.scroll_method.or(settings.scroll_method),
+                disable_while_typing: device
+                    .disable_while_typing
+                    .or(settings.disable_while_typing),
+                middle_emulation: device.middle_emulation.or(settings.middle_emulation),
+            };
+        }
+
+        settings
+    }
+
+    /// Name of the output a device called `name` of the given kinds is mapped to.
+    pub fn mapped_output(&self, name: &str, kinds: &[DeviceKind]) -> Option<&str> {
+        self.devices
+            .iter()
+            .rev()
+            .filter(|d| d.matches(name, kinds))
+            .find_map(|d| d.output.as_deref())
+    }
+
+    /// Region of its output a device called `name` of the given kinds is mapped to.
+    pub fn mapped_region(&self, name: &str, kinds: &[DeviceKind]) -> Option<[i32; 4]> {
+        self.devices
+            .iter()
+            .rev()
+            .filter(|d| d.matches(name, kinds))
+            .find_map(|d| d.region)
+    }
+}
+
+impl InputDeviceConfig {
+    pub fn matches(&self, name: &str, kinds: &[DeviceKind]) -> bool {
+        self.name.as_ref().is_none_or(|n| n == name)
+            && self.kind.is_none_or(|kind| kinds.contains(&kind))
+    }
+}
+
+impl PointerConfig {
+    pub fn drag_modifier(&self) -> Option<Modifiers> {
+        parse_modifier(&self.drag_modifier)
+    }
+}
+
+impl Keybinding {
+    /// The action run by the binding called `name`.
+    ///
+    /// Without an explicit `action`, a binding called `quit` still quits as it did before actions
+    /// existed, and everything else spawns `command`.
+    pub fn action(&self, name: &str) -> Action {
+        self.action
+            .clone()
+            .or_else(|| Action::from_binding_name(name))
+            .unwrap_or(Action::Spawn)
+    }
+}
+
+impl KeyboardConfig {
+    /// Index of the `[[keyboard.devices]]` entry for the keyboard called `name`, the last one
+    /// when several match.
+    pub fn device_index(&self, name: &str) -> Option<usize> {
+        self.devices.iter().rposition(|device| device.name == name)
+    }
+
+    /// The keymap settings of the `[[keyboard.devices]]` entry at `device`, or those of
+    /// `[keyboard]` itself for `None`.
+    pub fn keymap(&self, device: Option<usize>) -> KeyboardConfig {
+        match device.and_then(|idx| self.devices.get(idx)) {
+            Some(device) => KeyboardConfig {
+                layout: device.layout.clone(),
+                variant: device.variant.clone(),
+                options: device.options.clone(),
+                keymap_file: device.keymap_file.clone(),
+                devices: Vec::new(),
+                ..self.clone()
+            },
+            None => self.clone(),
+        }
+    }
+
+    pub fn xkb_config(&self) -> XkbConfig<'_> {
+        XkbConfig {
+            layout: &self.layout,
+            variant: &self.variant,
+            options: self.options.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+pub fn config_path() -> PathBuf {
     let config_dir: PathBuf = dirs::config_dir()
         .map(|p| p.join("clux"))
         .unwrap_or_else(|| "/etc/clux".into());
 
-    let config_path = config_dir.join("config.toml");
+    config_dir.join("config.toml")
+}
 
-    if let Ok(content) = fs::read_to_string(config_path) {
-        match toml::from_str(&content) {
-            Ok(config) => config,
-            Err(e) => {
-                tracing::error!("Config parsing error: {}", e);
-                CluxConfig::default()
-            }
-        }
-    } else {
-        CluxConfig::default()
+/// Reads and parses the config file at `path`.
+pub fn read_config(path: &Path) -> anyhow::Result<CluxConfig> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+
+    toml::from_str(&content).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+/// Reads the config file, or gives the defaults when there is none or it does not parse. An old
+/// config with a stray key still lets the session start, and saving it fixed reloads it.
+pub fn load_config() -> CluxConfig {
+    let config_path = config_path();
+
+    if !config_path.exists() {
+        return CluxConfig::default();
     }
+
+    read_config(&config_path).unwrap_or_else(|e| {
+        tracing::error!(
+            "Config parsing error, running with the default config until {} is fixed: {:#}",
+            config_path.display(),
+            e
+        );
+        CluxConfig::default()
+    })
 }
@@ -0,0 +1,295 @@
+//! The pointer cursor.
+//!
+//! Clients pick the cursor over their surfaces, either by attaching their own surface or by
+//! naming a cursor shape. Named cursors, and the cursor shown over the empty canvas, come from the
+//! configured xcursor theme. A tablet tool in proximity has a cursor of its own, picked the same
+//! way by the client under it.
+
+use std::collections::HashMap;
+
+use smithay::{
+    backend::{
+        allocator::Fourcc,
+        renderer::{
+            element::{
+                memory::{MemoryRenderBuffer, MemoryRenderBufferRenderElement},
+                solid::SolidColorRenderElement,
+                surface::render_elements_from_surface_tree,
+                Id, Kind,
+            },
+            utils::CommitCounter,
+            ImportAll, ImportMem, Renderer,
+        },
+    },
+    input::pointer::{CursorIcon, CursorImageStatus, CursorImageSurfaceData},
+    output::Output,
+    reexports::wayland_server::Resource,
+    utils::{Logical, Point, Rectangle, Size, Transform},
+    wayland::compositor::with_states,
+};
+use xcursor::{parser::parse_xcursor, CursorTheme};
+
+use crate::{config::CursorConfig, render::CluxRenderElement, Clux};
+
+/// Size of the square drawn when the theme has no usable cursor at all.
+const FALLBACK_SIZE: f64 = 8.0;
+const FALLBACK_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+/// One size of a themed cursor. Animated cursors only keep their first frame.
+struct CursorFrame {
+    /// Nominal size the frame was drawn for.
+    size: u32,
+    width: u32,
+    height: u32,
+    hotspot: (u32, u32),
+    buffer: MemoryRenderBuffer,
+}
+
+/// Cursor images loaded from the xcursor theme.
+pub struct Cursors {
+    theme: CursorTheme,
+    size: u32,
+    /// Frames of every icon asked for so far, `None` for the ones the theme lacks.
+    icons: HashMap<CursorIcon, Option<Vec<CursorFrame>>>,
+    fallback_id: Id,
+}
+
+impl Cursors {
+    pub fn new(config: &CursorConfig) -> Self {
+        let mut cursors = Self {
+            theme: CursorTheme::load(&config.theme),
+            size: config.size,
+            icons: HashMap::new(),
+            fallback_id: Id::new(),
+        };
+
+        cursors.load(CursorIcon::Default);
+        if cursors.frames(CursorIcon::Default).is_none() {
+            tracing::warn!(
+                "Cursor theme `{}` has no default cursor, drawing a square instead",
+                config.theme
+            );
+        }
+
+        cursors
+    }
+
+    /// Loads `icon` from the theme, unless that was tried already.
+    pub fn load(&mut self, icon: CursorIcon) {
+        if self.icons.contains_key(&icon) {
+            return;
+        }
+
+        let frames = std::iter::once(icon.name())
+            .chain(icon.alt_names().iter().copied())
+            .find_map(|name| load_frames(&self.theme, name));
+        self.icons.insert(icon, frames);
+    }
+
+    /// Frames of `icon`, falling back to the default cursor.
+    fn frames(&self, icon: CursorIcon) -> Option<&[CursorFrame]> {
+        self.icons
+            .get(&icon)
+            .and_then(Option::as_deref)
+            .or_else(|| self.icons.get(&CursorIcon::Default)?.as_deref())
+    }
+
+    fn render_elements<R>(
+        &self,
+        renderer: &mut R,
+        icon: CursorIcon,
+        location: Point<f64, Logical>,
+        scale: f64,
+    ) -> Vec<CluxRenderElement<R>>
+    where
+        R: Renderer + ImportAll + ImportMem,
+        R::TextureId: Send + Clone + 'static,
+    {
+        let Some(frames) = self.frames(icon) else {
+            let square = Rectangle::new(location, Size::from((FALLBACK_SIZE, FALLBACK_SIZE)));
+            return vec![SolidColorRenderElement::new(
+                self.fallback_id.clone(),
+                square.to_physical_precise_round(scale),
+                CommitCounter::default(),
+                FALLBACK_COLOR,
+                Kind::Cursor,
+            )
+            .into()];
+        };
+
+        // Use the frame drawn closest to the physical size, scaled to the configured size.
+        let wanted = (self.size as f64 * scale).round() as u32;
+        let frame = frames
+            .iter()
+            .min_by_key(|frame| frame.size.abs_diff(wanted))
+            .unwrap();
+        let ratio = self.size as f64 / frame.size as f64;
+        let hotspot: Point<f64, Logical> =
+            Point::from((frame.hotspot.0 as f64, frame.hotspot.1 as f64)).upscale(ratio);
+        let size: Size<i32, Logical> = Size::from((frame.width as f64, frame.height as f64))
+            .upscale(ratio)
+            .to_i32_round();
+
+        match MemoryRenderBufferRenderElement::from_buffer(
+            renderer,
+            (location - hotspot).to_physical(scale),
+            &frame.buffer,
+            None,
+            None,
+            Some(size),
+            Kind::Cursor,
+        ) {
+            Ok(element) => vec![element.into()],
+            Err(err) => {
+                tracing::warn!("Failed to upload the cursor: {:?}", err);
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// Reads every size of the cursor called `name` from `theme`.
+fn load_frames(theme: &CursorTheme, name: &str) -> Option<Vec<CursorFrame>> {
+    let path = theme.load_icon(name)?;
+    let data = std::fs::read(&path)
+        .map_err(|err| tracing::warn!("Failed to read {}: {}", path.display(), err))
+        .ok()?;
+    let images = parse_xcursor(&data)?;
+
+    let mut frames: Vec<CursorFrame> = Vec::new();
+    for image in images {
+        if frames.iter().any(|frame| frame.size == image.size) {
+            continue;
+        }
+
+        frames.push(CursorFrame {
+            size: image.size,
+            width: image.width,
+            height: image.height,
+            hotspot: (image.xhot, image.yhot),
+            buffer: MemoryRenderBuffer::from_slice(
+                &image.pixels_argb,
+                Fourcc::Argb8888,
+                (image.width as i32, image.height as i32),
+                1,
+                Transform::Normal,
+                None,
+            ),
+        });
+    }
+
+    (!frames.is_empty()).then_some(frames)
+}
+
+impl Clux {
+    /// Remembers the cursor asked for by the client under the pointer.
+    pub fn set_cursor_status(&mut self, status: CursorImageStatus) {
+        if let CursorImageStatus::Named(icon) = status {
+            self.cursors.load(icon);
+        }
+        self.cursor_status = status;
+    }
+
+    /// Remembers the cursor asked for by the client under the tablet tool.
+    pub fn set_tablet_cursor_status(&mut self, status: CursorImageStatus) {
+        if let CursorImageStatus::Named(icon) = status {
+            self.cursors.load(icon);
+        }
+        self.tablet_cursor_status = status;
+    }
+
+    /// Reloads the cursor theme, eg. after the config changed.
+    pub fn reload_cursors(&mut self) {
+        self.cursors = Cursors::new(&self.config.cursor);
+        for status in [&self.cursor_status, &self.tablet_cursor_status] {
+            if let CursorImageStatus::Named(icon) = status {
+                self.cursors.load(*icon);
+            }
+        }
+    }
+
+    /// Elements drawing the cursors on `output`: the tablet tool's above the pointer's.
+    pub fn cursor_render_elements<R>(
+        &self,
+        renderer: &mut R,
+        output: &Output,
+    ) -> Vec<CluxRenderElement<R>>
+    where
+        R: Renderer + ImportAll + ImportMem,
+        R::TextureId: Send + Clone + 'static,
+    {
+        let mut elements = Vec::new();
+
+        if let Some(pos) = self.tablet_tool_location {
+            elements.extend(self.cursor_elements_at(
+                renderer,
+                output,
+                &self.tablet_cursor_status,
+                pos,
+            ));
+        }
+        if let Some(pointer) = self.seat.get_pointer() {
+            elements.extend(self.cursor_elements_at(
+                renderer,
+                output,
+                &self.cursor_status,
+                pointer.current_location(),
+            ));
+        }
+
+        elements
+    }
+
+    /// Elements drawing the cursor `status` at canvas position `pos`, if that is on `output`.
+    fn cursor_elements_at<R>(
+        &self,
+        renderer: &mut R,
+        output: &Output,
+        status: &CursorImageStatus,
+        pos: Point<f64, Logical>,
+    ) -> Vec<CluxRenderElement<R>>
+    where
+        R: Renderer + ImportAll + ImportMem,
+        R::TextureId: Send + Clone + 'static,
+    {
+        if !self
+            .visible_canvas(output)
+            .is_some_and(|visible| visible.contains(pos))
+        {
+            return Vec::new();
+        }
+
+        // The cursor keeps its size whatever the zoom, so it is placed in output coordinates.
+        let location = self.viewport(output).to_local(pos);
+        let scale = output.current_scale().fractional_scale();
+
+        match status {
+            CursorImageStatus::Hidden => Vec::new(),
+            CursorImageStatus::Surface(surface) if surface.alive() => {
+                let hotspot = with_states(surface, |states| {
+                    states
+                        .data_map
+                        .get::<CursorImageSurfaceData>()
+                        .map(|data| data.lock().unwrap().hotspot)
+                        .unwrap_or_default()
+                });
+
+                render_elements_from_surface_tree(
+                    renderer,
+                    surface,
+                    (location - hotspot.to_f64()).to_physical_precise_round(scale),
+                    scale,
+                    1.0,
+                    Kind::Cursor,
+                )
+            }
+            CursorImageStatus::Surface(_) => {
+                self.cursors
+                    .render_elements(renderer, CursorIcon::Default, location, scale)
+            }
+            CursorImageStatus::Named(icon) => self
+                .cursors
+                .render_elements(renderer, *icon, location, scale),
+        }
+    }
+}
@@ -0,0 +1,459 @@
+//! Server-side decorations, for the windows whose clients ask for them through xdg-decoration or
+//! leave the choice to Clux.
+//!
+//! The titlebar sits above the window geometry, with the title on its left and the minimize,
+//! maximize and close buttons on its right, and the border goes around both. Dragging the
+//! titlebar moves the window, dragging the border resizes it from that edge. Maximized windows
+//! keep their titlebar but lose the border, fullscreen ones lose both.
+
+use std::cell::RefCell;
+
+use pangocairo::{
+    cairo::{self, Format, ImageSurface},
+    pango::{self, EllipsizeMode, FontDescription},
+};
+use smithay::{
+    backend::{
+        allocator::Fourcc,
+        renderer::{
+            element::{
+                memory::{MemoryRenderBuffer, MemoryRenderBufferRenderElement},
+                solid::{SolidColorBuffer, SolidColorRenderElement},
+                Kind,
+            },
+            ImportAll, ImportMem, Renderer,
+        },
+    },
+    desktop::{space::SpaceElement, Window},
+    output::Output,
+    reexports::wayland_protocols::xdg::{
+        decoration::zv1::server::zxdg_toplevel_decoration_v1::Mode, shell::server::xdg_toplevel,
+    },
+    utils::{Logical, Physical, Point, Rectangle, Transform},
+};
+
+use crate::{
+    config::Color, grabs::resize_grab::ResizeEdge, render::CluxRenderElement,
+    window::app_id_and_title, Clux,
+};
+
+/// Gap between the title and the left end of the titlebar, in logical pixels.
+const TITLE_PADDING: i32 = 8;
+/// Gap between a button's square and the edges of its part of the titlebar.
+const BUTTON_INSET: i32 = 6;
+
+/// A part of the decorations, as found under the pointer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecorationPart {
+    Titlebar,
+    Close,
+    Maximize,
+    Minimize,
+    /// The border, which resizes the window from these edges.
+    Border(ResizeEdge),
+}
+
+impl DecorationPart {
+    /// Titlebar buttons, from right to left.
+    const BUTTONS: [Self; 3] = [Self::Close, Self::Maximize, Self::Minimize];
+}
+
+/// Where the decorations of a window go, in canvas coordinates.
+pub struct Frame {
+    /// Window geometry the decorations surround.
+    window: Rectangle<i32, Logical>,
+    titlebar_height: i32,
+    border_width: i32,
+}
+
+impl Frame {
+    pub fn titlebar(&self) -> Rectangle<i32, Logical> {
+        Rectangle::new(
+            (self.window.loc.x, self.window.loc.y - self.titlebar_height).into(),
+            (self.window.size.w, self.titlebar_height).into(),
+        )
+    }
+
+    /// Everything the decorations take up, along with the window itself.
+    pub fn outer(&self) -> Rectangle<i32, Logical> {
+        let (border, titlebar) = (self.border_width, self.titlebar_height);
+        Rectangle::new(
+            (
+                self.window.loc.x - border,
+                self.window.loc.y - titlebar - border,
+            )
+                .into(),
+            (
+                self.window.size.w + 2 * border,
+                self.window.size.h + titlebar + 2 * border,
+            )
+                .into(),
+        )
+    }
+
+    /// The part of the titlebar button number `idx` reacts to, counting from the right.
+    fn button(&self, idx: usize) -> Rectangle<i32, Logical> {
+        let titlebar = self.titlebar();
+        let size = self.titlebar_height;
+        Rectangle::new(
+            (
+                titlebar.loc.x + titlebar.size.w - size * (idx as i32 + 1),
+                titlebar.loc.y,
+            )
+                .into(),
+            (size, size).into(),
+        )
+    }
+
+    /// The top, bottom, left and right sides of the border.
+    fn borders(&self) -> [Rectangle<i32, Logical>; 4] {
+        let outer = self.outer();
+        let border = self.border_width;
+        let side_height = outer.size.h - 2 * border;
+
+        [
+            Rectangle::new(outer.loc, (outer.size.w, border).into()),
+            Rectangle::new(
+                (outer.loc.x, outer.loc.y + outer.size.h - border).into(),
+                (outer.size.w, border).into(),
+            ),
+            Rectangle::new(
+                (outer.loc.x, outer.loc.y + border).into(),
+                (border, side_height).into(),
+            ),
+            Rectangle::new(
+                (outer.loc.x + outer.size.w - border, outer.loc.y + border).into(),
+                (border, side_height).into(),
+            ),
+        ]
+    }
+
+    /// The part of the decorations at the canvas position `pos`.
+    pub fn part_at(&self, pos: Point<f64, Logical>) -> Option<DecorationPart> {
+        if !self.outer().to_f64().contains(pos) || self.window.to_f64().contains(pos) {
+            return None;
+        }
+
+        let mut buttons = DecorationPart::BUTTONS.into_iter().enumerate();
+        if let Some((_, button)) = buttons.find(|(idx, _)| self.button(*idx).to_f64().contains(pos))
+        {
+            return Some(button);
+        }
+        if self.titlebar().to_f64().contains(pos) {
+            return Some(DecorationPart::Titlebar);
+        }
+
+        // Anything else is the border: find out which sides of the titlebar and window it is on.
+        let inner = self.titlebar().merge(self.window).to_f64();
+        let mut edges = ResizeEdge::empty();
+        if pos.x < inner.loc.x {
+            edges |= ResizeEdge::LEFT;
+        } else if pos.x >= inner.loc.x + inner.size.w {
+            edges |= ResizeEdge::RIGHT;
+        }
+        if pos.y < inner.loc.y {
+            edges |= ResizeEdge::TOP;
+        } else if pos.y >= inner.loc.y + inner.size.h {
+            edges |= ResizeEdge::BOTTOM;
+        }
+
+        Some(DecorationPart::Border(edges))
+    }
+}
+
+/// What the decorations of a window are drawn with, kept in its user data between frames so
+/// that only what changed gets redrawn.
+struct DecorationBuffers {
+    titlebar: SolidColorBuffer,
+    buttons: [SolidColorBuffer; 3],
+    borders: [SolidColorBuffer; 4],
+    /// The title as last drawn, along with what it was drawn for.
+    title: Option<(TitleKey, MemoryRenderBuffer, (i32, i32))>,
+}
+
+impl DecorationBuffers {
+    fn new() -> Self {
+        let buffer = || SolidColorBuffer::new((0, 0), [0.0; 4]);
+        Self {
+            titlebar: buffer(),
+            buttons: std::array::from_fn(|_| buffer()),
+            borders: std::array::from_fn(|_| buffer()),
+            title: None,
+        }
+    }
+}
+
+#[derive(PartialEq)]
+struct TitleKey {
+    text: String,
+    font: String,
+    color: Color,
+    /// Widest the title may be before it is cut short, in physical pixels.
+    max_width: i32,
+    scale: f64,
+}
+
+impl Color {
+    /// The color with its components multiplied by its alpha, as the renderers want it.
+    fn premultiplied(self) -> [f32; 4] {
+        let [r, g, b, a] = self.0;
+        [r * a, g * a, b * a, a]
+    }
+}
+
+/// Draws `text` in physical pixels, ellipsized past `max_width`. Returns the buffer and its size.
+fn render_text(
+    text: &str,
+    font: &str,
+    color: Color,
+    max_width: i32,
+    scale: f64,
+) -> anyhow::Result<(MemoryRenderBuffer, (i32, i32))> {
+    let mut font = FontDescription::from_string(font);
+    font.set_absolute_size(font.size() as f64 * scale);
+
+    // Lay the text out once on an empty surface to find out how big it is.
+    let surface = ImageSurface::create(Format::ARgb32, 0, 0)?;
+    let cr = cairo::Context::new(&surface)?;
+    let layout = pangocairo::functions::create_layout(&cr);
+    layout.set_font_description(Some(&font));
+    layout.set_width(max_width * pango::SCALE);
+    layout.set_ellipsize(EllipsizeMode::End);
+    layout.set_text(text);
+    let (width, height) = layout.pixel_size();
+    let (width, height) = (width.max(1), height.max(1));
+
+    let surface = ImageSurface::create(Format::ARgb32, width, height)?;
+    let cr = cairo::Context::new(&surface)?;
+    let [r, g, b, a] = color.0;
+    cr.set_source_rgba(r.into(), g.into(), b.into(), a.into());
+    pangocairo::functions::show_layout(&cr, &layout);
+    drop(cr);
+
+    let data = surface.take_data()?;
+    let buffer = MemoryRenderBuffer::from_slice(
+        &data,
+        Fourcc::Argb8888,
+        (width, height),
+        1,
+        Transform::Normal,
+        None,
+    );
+
+    Ok((buffer, (width, height)))
+}
+
+impl Clux {
+    /// Where the decorations of `window` go, `None` when it has none.
+    pub fn frame(&self, window: &Window) -> Option<Frame> {
+        let state = window.toplevel()?.current_state();
+        if state.decoration_mode != Some(Mode::ServerSide)
+            || state.states.contains(xdg_toplevel::State::Fullscreen)
+        {
+            return None;
+        }
+
+        let config = &self.config.decorations;
+        let border_width = if state.states.contains(xdg_toplevel::State::Maximized) {
+            0
+        } else {
+            config.border_width as i32
+        };
+
+        Some(Frame {
+            window: self.space.element_geometry(window)?,
+            titlebar_height: config.titlebar_height as i32,
+            border_width,
+        })
+    }
+
+    /// Everything drawn for `window`, its decorations included, in canvas coordinates.
+    pub fn decorated_bbox(&self, window: &Window) -> Option<Rectangle<i32, Logical>> {
+        let bbox = self.space.element_bbox(window)?;
+        Some(
+            self.frame(window)
+                .map_or(bbox, |frame| bbox.merge(frame.outer())),
+        )
+    }
+
+    /// The height of the titlebar `window` is about to get, going by its pending state. Windows
+    /// filling an area leave that much of it to the titlebar.
+    pub fn pending_titlebar_height(&self, window: &Window) -> i32 {
+        let decorated = window.toplevel().is_some_and(|toplevel| {
+            toplevel.with_pending_state(|pending| {
+                pending.decoration_mode == Some(Mode::ServerSide)
+                    && !pending.states.contains(xdg_toplevel::State::Fullscreen)
+            })
+        });
+
+        if decorated {
+            self.config.decorations.titlebar_height as i32
+        } else {
+            0
+        }
+    }
+
+    /// The decorations at the canvas position `pos`, unless a window above covers them there.
+    pub fn decoration_under(&self, pos: Point<f64, Logical>) -> Option<(Window, DecorationPart)> {
+        for window in self.space.elements().rev() {
+            let Some(location) = self.space.element_location(window) else {
+                continue;
+            };
+            let render_location = (location - window.geometry().loc).to_f64();
+            if window.is_in_input_region(&(pos - render_location)) {
+                return None;
+            }
+
+            let part = self.frame(window).and_then(|frame| frame.part_at(pos));
+            if let Some(part) = part {
+                return Some((window.clone(), part));
+            }
+        }
+
+        None
+    }
+
+    /// Runs the titlebar button `part` of `window`, once clicked.
+    pub fn click_decoration_button(&mut self, window: &Window, part: DecorationPart) {
+        match part {
+            DecorationPart::Close => {
+                if let Some(toplevel) = window.toplevel() {
+                    toplevel.send_close();
+                }
+            }
+            DecorationPart::Maximize => {
+                self.toggle_window_state(window, xdg_toplevel::State::Maximized)
+            }
+            DecorationPart::Minimize => self.minimize_window(window),
+            DecorationPart::Titlebar | DecorationPart::Border(_) => {}
+        }
+    }
+
+    /// Elements drawing the decorations of `window` on `output`, front to back.
+    pub fn decoration_render_elements<R>(
+        &self,
+        renderer: &mut R,
+        output: &Output,
+        window: &Window,
+    ) -> Vec<CluxRenderElement<R>>
+    where
+        R: Renderer + ImportAll + ImportMem,
+        R::TextureId: Send + Clone + 'static,
+    {
+        let Some(frame) = self.frame(window) else {
+            return Vec::new();
+        };
+        let config = &self.config.decorations;
+        let viewport = self.viewport(output);
+        let output_scale = output.current_scale().fractional_scale();
+        // Decorations scale with the canvas, like the windows they belong to.
+        let scale = output_scale * viewport.zoom;
+
+        window
+            .user_data()
+            .insert_if_missing(|| RefCell::new(DecorationBuffers::new()));
+        let mut buffers = window
+            .user_data()
+            .get::<RefCell<DecorationBuffers>>()
+            .unwrap()
+            .borrow_mut();
+
+        let to_physical = |loc: Point<i32, Logical>| -> Point<i32, Physical> {
+            viewport
+                .to_local(loc.to_f64())
+                .to_physical_precise_round(output_scale)
+        };
+        let solid = |buffer: &mut SolidColorBuffer, rect: Rectangle<i32, Logical>, color: Color| {
+            buffer.update(rect.size, color.premultiplied());
+            CluxRenderElement::from(SolidColorRenderElement::from_buffer(
+                buffer,
+                to_physical(rect.loc),
+                scale,
+                1.0,
+                Kind::Unspecified,
+            ))
+        };
+
+        let mut elements = Vec::new();
+        let buttons = [
+            config.close_color,
+            config.maximize_color,
+            config.minimize_color,
+        ];
+        for (idx, (buffer, color)) in buffers.buttons.iter_mut().zip(buttons).enumerate() {
+            let area = frame.button(idx);
+            let inset = BUTTON_INSET.min(area.size.w / 2 - 1).max(0);
+            let square = Rectangle::new(
+                area.loc + Point::from((inset, inset)),
+                (area.size.w - 2 * inset, area.size.h - 2 * inset).into(),
+            );
+            elements.push(solid(buffer, square, color));
+        }
+
+        let titlebar = frame.titlebar();
+        let title = app_id_and_title(window).1.unwrap_or_default();
+        let title_width = titlebar.size.w - 3 * titlebar.size.h - 2 * TITLE_PADDING;
+        if !title.is_empty() && title_width > 0 {
+            let key = TitleKey {
+                text: title,
+                font: config.font.clone(),
+                color: config.text_color,
+                max_width: (title_width as f64 * scale).round() as i32,
+                scale,
+            };
+            if buffers
+                .title
+                .as_ref()
+                .is_none_or(|(drawn, ..)| *drawn != key)
+            {
+                buffers.title = render_text(&key.text, &key.font, key.color, key.max_width, scale)
+                    .map_err(|err| tracing::warn!("Failed to draw a window title: {:#}", err))
+                    .ok()
+                    .map(|(buffer, size)| (key, buffer, size));
+            }
+
+            if let Some((_, buffer, (width, height))) = &buffers.title {
+                // Centered vertically, in canvas pixels for placing and output pixels for sizing.
+                let offset_y = (titlebar.size.h as f64 - *height as f64 / scale) / 2.0;
+                let loc = titlebar.loc.to_f64() + Point::from((TITLE_PADDING as f64, offset_y));
+                let size = (
+                    (*width as f64 / output_scale).round() as i32,
+                    (*height as f64 / output_scale).round() as i32,
+                );
+
+                match MemoryRenderBufferRenderElement::from_buffer(
+                    renderer,
+                    viewport.to_local(loc).to_physical(output_scale),
+                    buffer,
+                    None,
+                    None,
+                    Some(size.into()),
+                    Kind::Unspecified,
+                ) {
+                    Ok(element) => elements.push(element.into()),
+                    Err(err) => tracing::warn!("Failed to upload a window title: {:?}", err),
+                }
+            }
+        }
+
+        let focused = self.focused_window().as_ref() == Some(window);
+        let color = if focused {
+            config.focused_color
+        } else {
+            config.unfocused_color
+        };
+        let DecorationBuffers {
+            titlebar: titlebar_buffer,
+            borders,
+            ..
+        } = &mut *buffers;
+        elements.push(solid(titlebar_buffer, titlebar, color));
+        if frame.border_width > 0 {
+            for (buffer, side) in borders.iter_mut().zip(frame.borders()) {
+                elements.push(solid(buffer, side, color));
+            }
+        }
+
+        elements
+    }
+}
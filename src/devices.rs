@@ -0,0 +1,136 @@
+//! Applies the `[input]` config section to libinput devices.
+
+use smithay::reexports::input::{self as libinput, DeviceCapability, DeviceConfigError};
+
+use crate::{
+    config::{AccelProfile, DeviceKind, InputConfig, ScrollMethod},
+    Clux,
+};
+
+impl Clux {
+    /// Configures a newly plugged device and keeps it around for config reloads.
+    pub fn add_input_device(&mut self, mut device: libinput::Device) {
+        configure_device(&mut device, &self.config.input);
+        self.input_devices.push(device);
+    }
+
+    pub fn remove_input_device(&mut self, device: &libinput::Device) {
+        self.input_devices.retain(|d| d != device);
+    }
+
+    /// Applies the `[input]` settings to every device again, eg. after they changed.
+    pub fn configure_input_devices(&mut self) {
+        for device in &mut self.input_devices {
+            configure_device(device, &self.config.input);
+        }
+    }
+}
+
+/// The kinds a device counts as when matching `[[input.devices]]` entries.
+fn device_kinds(device: &libinput::Device) -> Vec<DeviceKind> {
+    let mut kinds = Vec::new();
+
+    if device.has_capability(DeviceCapability::Keyboard) {
+        kinds.push(DeviceKind::Keyboard);
+    }
+    if device.has_capability(DeviceCapability::Pointer) {
+        kinds.push(DeviceKind::Pointer);
+        // libinput only offers tapping on touchpads.
+        if device.config_tap_finger_count() > 0 {
+            kinds.push(DeviceKind::Touchpad);
+        }
+    }
+    if device.has_capability(DeviceCapability::Touch) {
+        kinds.push(DeviceKind::Touch);
+    }
+    if device.has_capability(DeviceCapability::TabletTool)
+        || device.has_capability(DeviceCapability::TabletPad)
+    {
+        kinds.push(DeviceKind::Tablet);
+    }
+
+    kinds
+}
+
+fn configure_device(device: &mut libinput::Device, config: &InputConfig) {
+    let name = device.name().to_string();
+    let settings = config.device_settings(&name, &device_kinds(device));
+
+    // Settings the config leaves out go back to libinput's defaults, so that taking one out of
+    // the config undoes it on the next reload.
+    let tap = settings
+        .tap
+        .unwrap_or_else(|| device.config_tap_default_enabled());
+    let natural_scroll = settings
+        .natural_scroll
+        .unwrap_or_else(|| device.config_scroll_default_natural_scroll_enabled());
+    let accel_profile = match settings.accel_profile {
+        Some(AccelProfile::Flat) => Some(libinput::AccelProfile::Flat),
+        Some(AccelProfile::Adaptive) => Some(libinput::AccelProfile::Adaptive),
+        None => device.config_accel_default_profile(),
+    };
+    let accel_speed = settings
+        .accel_speed
+        .unwrap_or_else(|| device.config_accel_default_speed());
+    let left_handed = settings
+        .left_handed
+        .unwrap_or_else(|| device.config_left_handed_default());
+    let scroll_method = match settings.scroll_method {
+        Some(ScrollMethod::NoScroll) => Some(libinput::ScrollMethod::NoScroll),
+        Some(ScrollMethod::TwoFinger) => Some(libinput::ScrollMethod::TwoFinger),
+        Some(ScrollMethod::Edge) => Some(libinput::ScrollMethod::Edge),
+        Some(ScrollMethod::OnButtonDown) => Some(libinput::ScrollMethod::OnButtonDown),
+        None => device.config_scroll_default_method(),
+    };
+    let disable_while_typing = settings
+        .disable_while_typing
+        .unwrap_or_else(|| device.config_dwt_default_enabled());
+    let middle_emulation = settings
+        .middle_emulation
+        .unwrap_or_else(|| device.config_middle_emulation_default_enabled());
+
+    let results = [
+        ("tap", Some(device.config_tap_set_enabled(tap))),
+        (
+            "natural_scroll",
+            Some(device.config_scroll_set_natural_scroll_enabled(natural_scroll)),
+        ),
+        (
+            "accel_profile",
+            accel_profile.map(|v| device.config_accel_set_profile(v)),
+        ),
+        (
+            "accel_speed",
+            Some(device.config_accel_set_speed(accel_speed)),
+        ),
+        (
+            "left_handed",
+            Some(device.config_left_handed_set(left_handed)),
+        ),
+        (
+            "scroll_method",
+            scroll_method.map(|v| device.config_scroll_set_method(v)),
+        ),
+        (
+            "disable_while_typing",
+            Some(device.config_dwt_set_enabled(disable_while_typing)),
+        ),
+        (
+            "middle_emulation",
+            Some(device.config_middle_emulation_set_enabled(middle_emulation)),
+        ),
+    ];
+
+    for (setting, result) in results {
+        match result {
+            // Global settings reach devices they make no sense for, eg. `tap` on a mouse.
+            Some(Err(DeviceConfigError::Unsupported)) => {
+                tracing::debug!("Input device `{}` does not support {}", name, setting);
+            }
+            Some(Err(DeviceConfigError::Invalid)) => {
+                tracing::warn!("Invalid {} for input device `{}`", setting, name);
+            }
+            _ => {}
+        }
+    }
+}
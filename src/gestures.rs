@@ -0,0 +1,434 @@
+//! Touchpad gestures, bound to actions or forwarded to clients.
+//!
+//! A gesture only finds its binding once it is clear which one it is: swipes once the fingers
+//! moved far enough to tell the direction, pinches once they moved far enough in or out. Panning
+//! and zooming then follow the fingers for the rest of the gesture, going the way the bound action
+//! does as the fingers move the way the gesture is bound for, while other actions run once.
+//!
+//! Gestures nothing is bound to go to the client under the pointer through the pointer-gestures
+//! protocol, by way of the pointer so that grabs see them too. When no binding could match, the
+//! client gets the gesture from its start; otherwise it is replayed once it turns out unbound.
+
+use smithay::{
+    input::pointer::{
+        GestureHoldBeginEvent, GestureHoldEndEvent, GesturePinchBeginEvent, GesturePinchEndEvent,
+        GesturePinchUpdateEvent, GestureSwipeBeginEvent, GestureSwipeEndEvent,
+        GestureSwipeUpdateEvent,
+    },
+    utils::{Logical, Point, SERIAL_COUNTER},
+};
+
+use crate::{
+    actions::Action,
+    bindings::{Binding, Gesture, SwipeDirection},
+    state::Clux,
+};
+
+/// Distance the fingers move before a swipe picks its direction, in touchpad pixels.
+const SWIPE_THRESHOLD: f64 = 16.0;
+/// Distance a swipe covers before an action that does not follow the fingers runs.
+const SWIPE_TRIGGER: f64 = 100.0;
+/// How far the pinch scale moves away from 1 before a pinch picks in or out.
+const PINCH_THRESHOLD: f64 = 0.15;
+
+/// The gesture currently on the touchpad.
+pub enum ActiveGesture {
+    Swipe {
+        fingers: u32,
+        /// Distance covered since the gesture began.
+        delta: Point<f64, Logical>,
+        progress: Progress,
+    },
+    Pinch {
+        fingers: u32,
+        /// Scale at the previous update, relative to the start of the gesture.
+        scale: f64,
+        /// Distance and rotation covered since the gesture began.
+        delta: Point<f64, Logical>,
+        rotation: f64,
+        progress: Progress,
+    },
+    Hold {
+        progress: Progress,
+    },
+}
+
+pub enum Progress {
+    /// Too early to tell which binding the gesture is.
+    Undecided,
+    /// Nothing is bound to the gesture, so it goes to the client.
+    Unbound,
+    /// Bound to an action that follows the fingers, for the gesture it is bound to.
+    Tracking(Gesture, Action),
+    /// Bound to an action that runs once, `None` once it ran.
+    Pending(Option<Binding>),
+}
+
+impl Progress {
+    fn new(gesture: Gesture, binding: Option<&Binding>) -> Self {
+        let Some(binding) = binding else {
+            return Self::Unbound;
+        };
+
+        let tracks = match gesture {
+            Gesture::Swipe { .. } => matches!(
+                binding.action,
+                Action::PanLeft | Action::PanRight | Action::PanUp | Action::PanDown
+            ),
+            Gesture::Pinch { .. } => matches!(binding.action, Action::ZoomIn | Action::ZoomOut),
+            Gesture::Hold { .. } => false,
+        };
+        if tracks {
+            Self::Tracking(gesture, binding.action.clone())
+        } else {
+            Self::Pending(Some(binding.clone()))
+        }
+    }
+}
+
+/// How far a swipe bound to a pan `action` pans when the fingers move by `delta`: as far as they
+/// moved in the direction the swipe is bound for, the way the action pans.
+fn swipe_pan(gesture: Gesture, action: &Action, delta: Point<f64, Logical>) -> Point<f64, Logical> {
+    let Gesture::Swipe { direction, .. } = gesture else {
+        return Point::default();
+    };
+    let along = match direction {
+        SwipeDirection::Left => -delta.x,
+        SwipeDirection::Right => delta.x,
+        SwipeDirection::Up => -delta.y,
+        SwipeDirection::Down => delta.y,
+    };
+
+    match action {
+        Action::PanLeft => (-along, 0.0),
+        Action::PanRight => (along, 0.0),
+        Action::PanUp => (0.0, -along),
+        Action::PanDown => (0.0, along),
+        _ => (0.0, 0.0),
+    }
+    .into()
+}
+
+/// How much a pinch bound to a zoom `action` zooms when the fingers spread by `factor`: zooming in
+/// or out as the action does while the fingers move the way the pinch is bound for.
+fn pinch_zoom(gesture: Gesture, action: &Action, factor: f64) -> f64 {
+    let Gesture::Pinch { outwards } = gesture else {
+        return 1.0;
+    };
+    let along = if outwards { factor } else { 1.0 / factor };
+
+    match action {
+        Action::ZoomIn => along,
+        Action::ZoomOut => 1.0 / along,
+        _ => 1.0,
+    }
+}
+
+impl Clux {
+    fn find_gesture(&self, gesture: Gesture) -> Option<&Binding> {
+        let modifiers = self.seat.get_keyboard().unwrap().modifier_state();
+        self.bindings.find_gesture(&self.mode, &modifiers, gesture)
+    }
+
+    /// Runs the binding of a gesture that runs once, unless it already did.
+    fn fire_gesture(&mut self) {
+        let pending = match &mut self.gesture {
+            Some(
+                ActiveGesture::Swipe { progress, .. }
+                | ActiveGesture::Pinch { progress, .. }
+                | ActiveGesture::Hold { progress },
+            ) => progress,
+            None => return,
+        };
+        if let Progress::Pending(binding) = pending {
+            if let Some(binding) = binding.take() {
+                self.run_action(binding.action, &binding.command);
+            }
+        }
+    }
+
+    pub fn swipe_begin(&mut self, fingers: u32, time: u32) {
+        let bound = SwipeDirection::NAMES.iter().any(|&(_, direction)| {
+            self.find_gesture(Gesture::Swipe { fingers, direction })
+                .is_some()
+        });
+        let progress = if bound {
+            Progress::Undecided
+        } else {
+            self.forward_swipe_begin(fingers, time);
+            Progress::Unbound
+        };
+
+        self.gesture = Some(ActiveGesture::Swipe {
+            fingers,
+            delta: Point::default(),
+            progress,
+        });
+    }
+
+    pub fn swipe_update(&mut self, delta: Point<f64, Logical>, time: u32) {
+        let Some(ActiveGesture::Swipe {
+            fingers,
+            delta: total,
+            progress,
+        }) = &mut self.gesture
+        else {
+            return;
+        };
+        *total += delta;
+        let (fingers, total) = (*fingers, *total);
+
+        if let Progress::Undecided = progress {
+            if total.x.hypot(total.y) < SWIPE_THRESHOLD {
+                return;
+            }
+
+            let direction = if total.x.abs() >= total.y.abs() {
+                if total.x < 0.0 {
+                    SwipeDirection::Left
+                } else {
+                    SwipeDirection::Right
+                }
+            } else if total.y < 0.0 {
+                SwipeDirection::Up
+            } else {
+                SwipeDirection::Down
+            };
+            let gesture = Gesture::Swipe { fingers, direction };
+            let decided = Progress::new(gesture, self.find_gesture(gesture));
+
+            // Catch up with the distance covered while undecided.
+            match &decided {
+                Progress::Tracking(gesture, action) => {
+                    self.pan_active_output(swipe_pan(*gesture, action, total))
+                }
+                Progress::Unbound => {
+                    self.forward_swipe_begin(fingers, time);
+                    self.forward_swipe_update(total, time);
+                }
+                _ => {}
+            }
+            if let Some(ActiveGesture::Swipe { progress, .. }) = &mut self.gesture {
+                *progress = decided;
+            }
+            return;
+        }
+
+        match progress {
+            Progress::Tracking(gesture, action) => {
+                let pan = swipe_pan(*gesture, action, delta);
+                self.pan_active_output(pan);
+            }
+            Progress::Pending(_) if total.x.hypot(total.y) >= SWIPE_TRIGGER => self.fire_gesture(),
+            Progress::Unbound => self.forward_swipe_update(delta, time),
+            _ => {}
+        }
+    }
+
+    pub fn swipe_end(&mut self, cancelled: bool, time: u32) {
+        // A swipe that picked its action but ended short of the trigger distance still counts.
+        if !cancelled {
+            self.fire_gesture();
+        }
+        if let Some(ActiveGesture::Swipe {
+            progress: Progress::Unbound,
+            ..
+        }) = self.gesture.take()
+        {
+            let pointer = self.seat.get_pointer().unwrap();
+            pointer.gesture_swipe_end(
+                self,
+                &GestureSwipeEndEvent {
+                    serial: SERIAL_COUNTER.next_serial(),
+                    time,
+                    cancelled,
+                },
+            );
+        }
+    }
+
+    pub fn pinch_begin(&mut self, fingers: u32, time: u32) {
+        let bound = [false, true]
+            .into_iter()
+            .any(|outwards| self.find_gesture(Gesture::Pinch { outwards }).is_some());
+        let progress = if bound {
+            Progress::Undecided
+        } else {
+            self.forward_pinch_begin(fingers, time);
+            Progress::Unbound
+        };
+
+        self.gesture = Some(ActiveGesture::Pinch {
+            fingers,
+            scale: 1.0,
+            delta: Point::default(),
+            rotation: 0.0,
+            progress,
+        });
+    }
+
+    /// `scale` is relative to the distance between the fingers when the pinch began, `rotation`
+    /// is in degrees since the previous update.
+    pub fn pinch_update(
+        &mut self,
+        delta: Point<f64, Logical>,
+        scale: f64,
+        rotation: f64,
+        time: u32,
+    ) {
+        let Some(ActiveGesture::Pinch {
+            fingers,
+            scale: previous,
+            delta: total_delta,
+            rotation: total_rotation,
+            progress,
+        }) = &mut self.gesture
+        else {
+            return;
+        };
+        let factor = scale / *previous;
+        *previous = scale;
+        *total_delta += delta;
+        *total_rotation += rotation;
+        let (fingers, total_delta, total_rotation) = (*fingers, *total_delta, *total_rotation);
+
+        match progress {
+            Progress::Undecided => {
+                if (scale - 1.0).abs() < PINCH_THRESHOLD {
+                    return;
+                }
+
+                let gesture = Gesture::Pinch {
+                    outwards: scale > 1.0,
+                };
+                let decided = Progress::new(gesture, self.find_gesture(gesture));
+
+                match &decided {
+                    Progress::Tracking(gesture, action) => {
+                        self.zoom_active_output(pinch_zoom(*gesture, action, scale))
+                    }
+                    Progress::Unbound => {
+                        self.forward_pinch_begin(fingers, time);
+                        self.forward_pinch_update(total_delta, scale, total_rotation, time);
+                    }
+                    _ => {}
+                }
+                if let Some(ActiveGesture::Pinch { progress, .. }) = &mut self.gesture {
+                    *progress = decided;
+                }
+                self.fire_gesture();
+            }
+            Progress::Tracking(gesture, action) => {
+                let zoom = pinch_zoom(*gesture, action, factor);
+                self.zoom_active_output(zoom);
+            }
+            Progress::Unbound => self.forward_pinch_update(delta, scale, rotation, time),
+            _ => {}
+        }
+    }
+
+    pub fn pinch_end(&mut self, cancelled: bool, time: u32) {
+        if let Some(ActiveGesture::Pinch {
+            progress: Progress::Unbound,
+            ..
+        }) = self.gesture.take()
+        {
+            let pointer = self.seat.get_pointer().unwrap();
+            pointer.gesture_pinch_end(
+                self,
+                &GesturePinchEndEvent {
+                    serial: SERIAL_COUNTER.next_serial(),
+                    time,
+                    cancelled,
+                },
+            );
+        }
+    }
+
+    pub fn hold_begin(&mut self, fingers: u32, time: u32) {
+        let gesture = Gesture::Hold { fingers };
+        let progress = Progress::new(gesture, self.find_gesture(gesture));
+
+        if let Progress::Unbound = progress {
+            let pointer = self.seat.get_pointer().unwrap();
+            pointer.gesture_hold_begin(
+                self,
+                &GestureHoldBeginEvent {
+                    serial: SERIAL_COUNTER.next_serial(),
+                    time,
+                    fingers,
+                },
+            );
+        }
+        self.gesture = Some(ActiveGesture::Hold { progress });
+    }
+
+    /// A cancelled hold is one that turned into another gesture, or the fingers moved.
+    pub fn hold_end(&mut self, cancelled: bool, time: u32) {
+        if !cancelled {
+            self.fire_gesture();
+        }
+        if let Some(ActiveGesture::Hold {
+            progress: Progress::Unbound,
+        }) = self.gesture.take()
+        {
+            let pointer = self.seat.get_pointer().unwrap();
+            pointer.gesture_hold_end(
+                self,
+                &GestureHoldEndEvent {
+                    serial: SERIAL_COUNTER.next_serial(),
+                    time,
+                    cancelled,
+                },
+            );
+        }
+    }
+
+    fn forward_swipe_begin(&mut self, fingers: u32, time: u32) {
+        let pointer = self.seat.get_pointer().unwrap();
+        pointer.gesture_swipe_begin(
+            self,
+            &GestureSwipeBeginEvent {
+                serial: SERIAL_COUNTER.next_serial(),
+                time,
+                fingers,
+            },
+        );
+    }
+
+    fn forward_swipe_update(&mut self, delta: Point<f64, Logical>, time: u32) {
+        let pointer = self.seat.get_pointer().unwrap();
+        pointer.gesture_swipe_update(self, &GestureSwipeUpdateEvent { time, delta });
+    }
+
+    fn forward_pinch_begin(&mut self, fingers: u32, time: u32) {
+        let pointer = self.seat.get_pointer().unwrap();
+        pointer.gesture_pinch_begin(
+            self,
+            &GesturePinchBeginEvent {
+                serial: SERIAL_COUNTER.next_serial(),
+                time,
+                fingers,
+            },
+        );
+    }
+
+    fn forward_pinch_update(
+        &mut self,
+        delta: Point<f64, Logical>,
+        scale: f64,
+        rotation: f64,
+        time: u32,
+    ) {
+        let pointer = self.seat.get_pointer().unwrap();
+        pointer.gesture_pinch_update(
+            self,
+            &GesturePinchUpdateEvent {
+                time,
+                delta,
+                scale,
+                rotation,
+            },
+        );
+    }
+}
@@ -3,3 +3,9 @@ pub use move_grab::MoveSurfaceGrab;
 
 pub mod resize_grab;
 pub use resize_grab::ResizeSurfaceGrab;
+
+pub mod pan_grab;
+pub use pan_grab::PanGrab;
+
+pub mod touch_move_grab;
+pub use touch_move_grab::TouchMoveSurfaceGrab;
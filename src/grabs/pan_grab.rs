@@ -0,0 +1,162 @@
+//! Pan grab is the state of a composer during which the canvas is being dragged around.
+//!
+//! eg. Whenever the user holds the pan modifier and drags with the pointer, the viewport of the
+//! output under the pointer follows the drag.
+
+use crate::Clux;
+use smithay::{
+    input::pointer::{
+        AxisFrame, ButtonEvent, GestureHoldBeginEvent, GestureHoldEndEvent, GesturePinchBeginEvent,
+        GesturePinchEndEvent, GesturePinchUpdateEvent, GestureSwipeBeginEvent, GestureSwipeEndEvent,
+        GestureSwipeUpdateEvent, GrabStartData as PointerGrabStartData, MotionEvent, PointerGrab,
+        PointerInnerHandle, RelativeMotionEvent,
+    },
+    output::Output,
+    reexports::wayland_server::protocol::wl_surface::WlSurface,
+    utils::{Logical, Point},
+};
+
+pub struct PanGrab {
+    pub start_data: PointerGrabStartData<Clux>,
+    pub output: Output,
+}
+
+impl PointerGrab<Clux> for PanGrab {
+    fn motion(
+        &mut self,
+        data: &mut Clux,
+        handle: &mut PointerInnerHandle<'_, Clux>,
+        _focus: Option<(WlSurface, Point<f64, Logical>)>,
+        event: &MotionEvent,
+    ) {
+        // Move the viewport so the canvas point grabbed at the start stays under the pointer.
+        let anchor = self.start_data.location;
+        data.pan_output(&self.output, anchor - event.location);
+
+        // While the grab is active, no client has pointer focus
+        handle.motion(
+            data,
+            None,
+            &MotionEvent {
+                location: anchor,
+                serial: event.serial,
+                time: event.time,
+            },
+        );
+    }
+
+    fn relative_motion(
+        &mut self,
+        data: &mut Clux,
+        handle: &mut PointerInnerHandle<'_, Clux>,
+        focus: Option<(WlSurface, Point<f64, Logical>)>,
+        event: &RelativeMotionEvent,
+    ) {
+        handle.relative_motion(data, focus, event);
+    }
+
+    fn button(
+        &mut self,
+        data: &mut Clux,
+        handle: &mut PointerInnerHandle<'_, Clux>,
+        event: &ButtonEvent,
+    ) {
+        handle.button(data, event);
+
+        if !handle.current_pressed().contains(&self.start_data.button) {
+            // The button that started the drag is released, release the grab.
+            handle.unset_grab(self, data, event.serial, event.time, true);
+        }
+    }
+
+    fn axis(
+        &mut self,
+        data: &mut Clux,
+        handle: &mut PointerInnerHandle<'_, Clux>,
+        details: AxisFrame,
+    ) {
+        handle.axis(data, details)
+    }
+
+    fn frame(&mut self, data: &mut Clux, handle: &mut PointerInnerHandle<'_, Clux>) {
+        handle.frame(data);
+    }
+
+    fn gesture_swipe_begin(
+        &mut self,
+        data: &mut Clux,
+        handle: &mut PointerInnerHandle<'_, Clux>,
+        event: &GestureSwipeBeginEvent,
+    ) {
+        handle.gesture_swipe_begin(data, event)
+    }
+
+    fn gesture_swipe_update(
+        &mut self,
+        data: &mut Clux,
+        handle: &mut PointerInnerHandle<'_, Clux>,
+        event: &GestureSwipeUpdateEvent,
+    ) {
+        handle.gesture_swipe_update(data, event)
+    }
+
+    fn gesture_swipe_end(
+        &mut self,
+        data: &mut Clux,
+        handle: &mut PointerInnerHandle<'_, Clux>,
+        event: &GestureSwipeEndEvent,
+    ) {
+        handle.gesture_swipe_end(data, event)
+    }
+
+    fn gesture_pinch_begin(
+        &mut self,
+        data: &mut Clux,
+        handle: &mut PointerInnerHandle<'_, Clux>,
+        event: &GesturePinchBeginEvent,
+    ) {
+        handle.gesture_pinch_begin(data, event)
+    }
+
+    fn gesture_pinch_update(
+        &mut self,
+        data: &mut Clux,
+        handle: &mut PointerInnerHandle<'_, Clux>,
+        event: &GesturePinchUpdateEvent,
+    ) {
+        handle.gesture_pinch_update(data, event)
+    }
+
+    fn gesture_pinch_end(
+        &mut self,
+        data: &mut Clux,
+        handle: &mut PointerInnerHandle<'_, Clux>,
+        event: &GesturePinchEndEvent,
+    ) {
+        handle.gesture_pinch_end(data, event)
+    }
+
+    fn gesture_hold_begin(
+        &mut self,
+        data: &mut Clux,
+        handle: &mut PointerInnerHandle<'_, Clux>,
+        event: &GestureHoldBeginEvent,
+    ) {
+        handle.gesture_hold_begin(data, event)
+    }
+
+    fn gesture_hold_end(
+        &mut self,
+        data: &mut Clux,
+        handle: &mut PointerInnerHandle<'_, Clux>,
+        event: &GestureHoldEndEvent,
+    ) {
+        handle.gesture_hold_end(data, event)
+    }
+
+    fn start_data(&self) -> &PointerGrabStartData<Clux> {
+        &self.start_data
+    }
+
+    fn unset(&mut self, _data: &mut Clux) {}
+}
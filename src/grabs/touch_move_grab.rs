@@ -0,0 +1,102 @@
+//! Moving a window with a finger, started by a client's move request after a touch down.
+
+use crate::Clux;
+use smithay::{
+    desktop::Window,
+    input::touch::{
+        DownEvent, GrabStartData as TouchGrabStartData, MotionEvent, OrientationEvent, ShapeEvent,
+        TouchGrab, TouchInnerHandle, UpEvent,
+    },
+    reexports::wayland_server::protocol::wl_surface::WlSurface,
+    utils::{Logical, Point, Serial},
+};
+
+pub struct TouchMoveSurfaceGrab {
+    pub start_data: TouchGrabStartData<Clux>,
+    pub window: Window,
+    pub initial_window_location: Point<i32, Logical>,
+}
+
+impl TouchGrab<Clux> for TouchMoveSurfaceGrab {
+    fn down(
+        &mut self,
+        data: &mut Clux,
+        handle: &mut TouchInnerHandle<'_, Clux>,
+        _focus: Option<(WlSurface, Point<f64, Logical>)>,
+        event: &DownEvent,
+        seq: Serial,
+    ) {
+        // Other fingers keep reaching whatever the first one touched.
+        handle.down(data, None, event, seq);
+    }
+
+    fn up(
+        &mut self,
+        data: &mut Clux,
+        handle: &mut TouchInnerHandle<'_, Clux>,
+        event: &UpEvent,
+        seq: Serial,
+    ) {
+        handle.up(data, event, seq);
+
+        // The grab lasts as long as the finger that started it stays down.
+        if event.slot == self.start_data.slot {
+            handle.unset_grab(self, data);
+        }
+    }
+
+    fn motion(
+        &mut self,
+        data: &mut Clux,
+        handle: &mut TouchInnerHandle<'_, Clux>,
+        _focus: Option<(WlSurface, Point<f64, Logical>)>,
+        event: &MotionEvent,
+        seq: Serial,
+    ) {
+        handle.motion(data, None, event, seq);
+
+        if event.slot != self.start_data.slot {
+            return;
+        }
+
+        let delta = event.location - self.start_data.location;
+        let new_location = self.initial_window_location.to_f64() + delta;
+        data.space
+            .map_element(self.window.clone(), new_location.to_i32_round(), true);
+    }
+
+    fn frame(&mut self, data: &mut Clux, handle: &mut TouchInnerHandle<'_, Clux>, seq: Serial) {
+        handle.frame(data, seq);
+    }
+
+    fn cancel(&mut self, data: &mut Clux, handle: &mut TouchInnerHandle<'_, Clux>, seq: Serial) {
+        handle.cancel(data, seq);
+        handle.unset_grab(self, data);
+    }
+
+    fn shape(
+        &mut self,
+        data: &mut Clux,
+        handle: &mut TouchInnerHandle<'_, Clux>,
+        event: &ShapeEvent,
+        seq: Serial,
+    ) {
+        handle.shape(data, event, seq);
+    }
+
+    fn orientation(
+        &mut self,
+        data: &mut Clux,
+        handle: &mut TouchInnerHandle<'_, Clux>,
+        event: &OrientationEvent,
+        seq: Serial,
+    ) {
+        handle.orientation(data, event, seq);
+    }
+
+    fn start_data(&self) -> &TouchGrabStartData<Clux> {
+        &self.start_data
+    }
+
+    fn unset(&mut self, _data: &mut Clux) {}
+}
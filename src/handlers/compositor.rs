@@ -37,8 +37,10 @@ impl CompositorHandler for Clux {
                 .space
                 .elements()
                 .find(|w| w.toplevel().unwrap().wl_surface() == &root)
+                .cloned()
             {
                 window.on_commit();
+                self.place_new_window(&window);
             }
         };
 
@@ -8,7 +8,7 @@ use crate::Clux;
 //
 
 use smithay::input::dnd::{DnDGrab, DndGrabHandler, GrabType, Source};
-use smithay::input::pointer::Focus;
+use smithay::input::pointer::{CursorImageStatus, Focus};
 use smithay::input::{Seat, SeatHandler, SeatState};
 use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
 use smithay::reexports::wayland_server::Resource;
@@ -18,7 +18,11 @@ use smithay::wayland::selection::data_device::{
     set_data_device_focus, DataDeviceHandler, DataDeviceState, WaylandDndGrabHandler,
 };
 use smithay::wayland::selection::SelectionHandler;
-use smithay::{delegate_data_device, delegate_output, delegate_seat};
+use smithay::wayland::tablet_manager::{TabletSeatHandler, TabletToolDescriptor};
+use smithay::{
+    delegate_data_device, delegate_output, delegate_pointer_gestures, delegate_relative_pointer,
+    delegate_seat, delegate_tablet_manager,
+};
 
 impl SeatHandler for Clux {
     type KeyboardFocus = WlSurface;
@@ -29,17 +33,44 @@ impl SeatHandler for Clux {
         &mut self.seat_state
     }
 
-    fn cursor_image(&mut self, _seat: &Seat<Self>, _image: smithay::input::pointer::CursorImageStatus) {}
+    fn cursor_image(&mut self, _seat: &Seat<Self>, image: CursorImageStatus) {
+        self.set_cursor_status(image);
+    }
 
     fn focus_changed(&mut self, seat: &Seat<Self>, focused: Option<&WlSurface>) {
         let dh = &self.display_handle;
         let client = focused.and_then(|s| dh.get_client(s.id()).ok());
         set_data_device_focus(dh, seat, client);
+        self.queue_window_layout(focused);
     }
 }
 
 delegate_seat!(Clux);
 
+//
+// Relative Pointer
+//
+
+delegate_relative_pointer!(Clux);
+
+//
+// Pointer Gestures
+//
+
+delegate_pointer_gestures!(Clux);
+
+//
+// Tablet Manager
+//
+
+impl TabletSeatHandler for Clux {
+    fn tablet_tool_image(&mut self, _tool: &TabletToolDescriptor, image: CursorImageStatus) {
+        self.set_tablet_cursor_status(image);
+    }
+}
+
+delegate_tablet_manager!(Clux);
+
 //
 // Wl Data Device
 //
@@ -74,8 +105,11 @@ impl WaylandDndGrabHandler for Clux {
                 ptr.set_grab(self, grab, serial, Focus::Keep);
             }
             GrabType::Touch => {
-                // clux lacks touch handling
-                source.cancel();
+                let touch = seat.get_touch().unwrap();
+                let start_data = touch.grab_start_data().unwrap();
+
+                let grab = DnDGrab::new_touch(&self.display_handle, start_data, source, seat);
+                touch.set_grab(self, grab, serial);
             }
         }
     }
@@ -1,12 +1,16 @@
 use smithay::{
-    delegate_xdg_shell,
+    delegate_xdg_decoration, delegate_xdg_shell,
     desktop::{find_popup_root_surface, get_popup_toplevel_coords, PopupKind, PopupManager, Space, Window},
     input::{
         pointer::{Focus, GrabStartData as PointerGrabStartData},
+        touch::GrabStartData as TouchGrabStartData,
         Seat,
     },
     reexports::{
-        wayland_protocols::xdg::shell::server::xdg_toplevel,
+        wayland_protocols::xdg::{
+            decoration::zv1::server::zxdg_toplevel_decoration_v1::Mode as DecorationMode,
+            shell::server::xdg_toplevel,
+        },
         wayland_server::{
             protocol::{wl_seat, wl_surface::WlSurface},
             Resource,
@@ -16,14 +20,16 @@ use smithay::{
     wayland::{
         compositor::with_states,
         shell::xdg::{
-            PopupSurface, PositionerState, ToplevelSurface, XdgShellHandler, XdgShellState,
-            XdgToplevelSurfaceData,
+            decoration::XdgDecorationHandler, PopupSurface, PositionerState, ToplevelSurface,
+            XdgShellHandler, XdgShellState, XdgToplevelSurfaceData,
         },
     },
 };
 
 use crate::{
-    grabs::{MoveSurfaceGrab, ResizeSurfaceGrab},
+    config::DecorationMode as ConfigDecorationMode,
+    grabs::{MoveSurfaceGrab, ResizeSurfaceGrab, TouchMoveSurfaceGrab},
+    window::WindowId,
     Clux,
 };
 
@@ -34,7 +40,15 @@ impl XdgShellHandler for Clux {
 
     fn new_toplevel(&mut self, surface: ToplevelSurface) {
         let window = Window::new_wayland_window(surface);
-        self.space.map_element(window, (0, 0), false);
+        WindowId::of(&window);
+
+        // Open in the top-left corner of what the active output shows, wherever it is panned to.
+        let location = self
+            .active_output()
+            .and_then(|output| self.visible_canvas(&output))
+            .map(|visible| visible.loc.to_i32_round())
+            .unwrap_or_default();
+        self.space.map_element(window, location, false);
     }
 
     fn new_popup(&mut self, surface: PopupSurface, _positioner: PositionerState) {
@@ -75,6 +89,21 @@ impl XdgShellHandler for Clux {
             };
 
             pointer.set_grab(self, grab, serial, Focus::Clear);
+        } else if let Some(start_data) = check_touch_grab(&seat, wl_surface, serial) {
+            let touch = seat.get_touch().unwrap();
+
+            let Some(window) = self.window_for_surface(wl_surface) else {
+                return;
+            };
+            let initial_window_location = self.space.element_location(&window).unwrap();
+
+            let grab = TouchMoveSurfaceGrab {
+                start_data,
+                window,
+                initial_window_location,
+            };
+
+            touch.set_grab(self, grab, serial);
         }
     }
 
@@ -118,6 +147,12 @@ impl XdgShellHandler for Clux {
         }
     }
 
+    fn minimize_request(&mut self, surface: ToplevelSurface) {
+        if let Some(window) = self.window_for_surface(surface.wl_surface()) {
+            self.minimize_window(&window);
+        }
+    }
+
     fn grab(&mut self, _surface: PopupSurface, _seat: wl_seat::WlSeat, _serial: Serial) {
         // TODO popup grabs
     }
@@ -126,6 +161,39 @@ impl XdgShellHandler for Clux {
 // Xdg Shell
 delegate_xdg_shell!(Clux);
 
+impl XdgDecorationHandler for Clux {
+    fn new_decoration(&mut self, toplevel: ToplevelSurface) {
+        let mode = self.default_decoration_mode();
+        set_decoration_mode(&toplevel, mode);
+    }
+
+    fn request_mode(&mut self, toplevel: ToplevelSurface, mode: DecorationMode) {
+        set_decoration_mode(&toplevel, mode);
+    }
+
+    fn unset_mode(&mut self, toplevel: ToplevelSurface) {
+        let mode = self.default_decoration_mode();
+        set_decoration_mode(&toplevel, mode);
+    }
+}
+
+// Xdg Decoration
+delegate_xdg_decoration!(Clux);
+
+/// Marks the windows `Clux::place_new_window` is done with.
+struct Placed;
+
+/// Configures `toplevel` with decorations drawn the `mode` way, unless it waits for its first
+/// configure anyway.
+fn set_decoration_mode(toplevel: &ToplevelSurface, mode: DecorationMode) {
+    toplevel.with_pending_state(|state| {
+        state.decoration_mode = Some(mode);
+    });
+    if toplevel.is_initial_configure_sent() {
+        toplevel.send_pending_configure();
+    }
+}
+
 fn check_grab(
     seat: &Seat<Clux>,
     surface: &WlSurface,
@@ -149,6 +217,28 @@ fn check_grab(
     Some(start_data)
 }
 
+fn check_touch_grab(
+    seat: &Seat<Clux>,
+    surface: &WlSurface,
+    serial: Serial,
+) -> Option<TouchGrabStartData<Clux>> {
+    let touch = seat.get_touch()?;
+
+    // Check that this surface was touched.
+    if !touch.has_grab(serial) {
+        return None;
+    }
+
+    let start_data = touch.grab_start_data()?;
+
+    let (focus, _) = start_data.focus.as_ref()?;
+    if !focus.id().same_client_as(&surface.id()) {
+        return None;
+    }
+
+    Some(start_data)
+}
+
 /// Should be called on `WlSurface::commit`
 pub fn handle_commit(popups: &mut PopupManager, space: &Space<Window>, surface: &WlSurface) {
     // Handle toplevel commits.
@@ -189,6 +279,31 @@ pub fn handle_commit(popups: &mut PopupManager, space: &Space<Window>, surface:
 }
 
 impl Clux {
+    /// Decorations of the clients that leave the choice to the compositor.
+    fn default_decoration_mode(&self) -> DecorationMode {
+        match self.config.decorations.mode {
+            ConfigDecorationMode::Server => DecorationMode::ServerSide,
+            ConfigDecorationMode::Client => DecorationMode::ClientSide,
+        }
+    }
+
+    /// Moves a window showing its first buffer so that its decorations, known by now, start at
+    /// the corner it was mapped at instead of reaching past it.
+    pub fn place_new_window(&mut self, window: &Window) {
+        if window.geometry().is_empty() || window.user_data().get::<Placed>().is_some() {
+            return;
+        }
+        window.user_data().insert_if_missing_threadsafe(|| Placed);
+
+        let (Some(frame), Some(location)) =
+            (self.frame(window), self.space.element_location(window))
+        else {
+            return;
+        };
+        let offset = location - frame.outer().loc;
+        self.space.map_element(window.clone(), location + offset, false);
+    }
+
     fn unconstrain_popup(&self, popup: &PopupSurface) {
         let Ok(root) = find_popup_root_surface(&PopupKind::Xdg(popup.clone())) else {
             return;
@@ -202,12 +317,12 @@ impl Clux {
         };
 
         let output = self.space.outputs().next().unwrap();
-        let output_geo = self.space.output_geometry(output).unwrap();
+        let visible = self.visible_canvas(output).unwrap().to_i32_round();
         let window_geo = self.space.element_geometry(window).unwrap();
 
         // The target geometry for the positioner should be relative to its parent's geometry, so
         // we will compute that here.
-        let mut target = output_geo;
+        let mut target = visible;
         target.loc -= get_popup_toplevel_coords(&PopupKind::Xdg(popup.clone()));
         target.loc -= window_geo.loc;
 
@@ -1,25 +1,53 @@
 use smithay::{
     backend::input::{
-        AbsolutePositionEvent, Event, InputBackend, InputEvent, KeyState, KeyboardKeyEvent,
-        PointerButtonEvent,
+        AbsolutePositionEvent, Axis, AxisSource, ButtonState, Device, Event, GestureBeginEvent,
+        GestureEndEvent, GesturePinchUpdateEvent, GestureSwipeUpdateEvent, InputBackend,
+        InputEvent, KeyState, KeyboardKeyEvent, PointerAxisEvent, PointerButtonEvent,
+        PointerMotionEvent, TouchEvent,
     },
+    desktop::Window,
     input::{
         keyboard::{FilterResult, Keysym},
-        pointer::{ButtonEvent, MotionEvent},
+        pointer::{
+            AxisFrame, ButtonEvent, Focus, GrabStartData as PointerGrabStartData, MotionEvent,
+            RelativeMotionEvent,
+        },
+        touch::{DownEvent, MotionEvent as TouchMotionEvent, UpEvent},
     },
-    utils::SERIAL_COUNTER,
+    output::Output,
+    utils::{Logical, Point, Serial, SERIAL_COUNTER},
 };
 
-use crate::state::Clux;
+use crate::{
+    bindings::{Binding, Modifiers, ScrollDirection},
+    config::DeviceKind,
+    decorations::DecorationPart,
+    grabs::{resize_grab::ResizeEdge, MoveSurfaceGrab, PanGrab, ResizeSurfaceGrab},
+    state::Clux,
+};
+
+/// Scroll distance that runs a scroll binding once: a single wheel notch, in v120 units.
+const SCROLL_STEP: f64 = 120.0;
+
+// Button codes as defined in the Linux kernel's linux/input-event-codes.h header file.
+const BTN_LEFT: u32 = 0x110;
+const BTN_RIGHT: u32 = 0x111;
+const BTN_MIDDLE: u32 = 0x112;
 
 impl Clux {
     pub fn process_input_event<I: InputBackend>(&mut self, event: InputEvent<I>) {
         match event {
             InputEvent::Keyboard { event, .. } => {
+                // The key is read with the layout of the keyboard it was typed on.
+                self.switch_keymap_for(&event.device().name());
+
                 let serial = SERIAL_COUNTER.next_serial();
                 let time = Event::time_msec(&event);
+                // Bindings run once the keyboard is done with the event, as actions may change
+                // the keyboard focus.
+                let mut fired = None;
 
-                self.seat.get_keyboard().unwrap().input::<(), _>(
+                let overview_key = self.seat.get_keyboard().unwrap().input::<Option<Keysym>, _>(
                     self,
                     event.key_code(),
                     event.state(),
@@ -28,77 +56,89 @@ impl Clux {
                     |state, modifiers, handle| {
                         let keysym = handle.modified_sym();
 
-                        if event.state() == KeyState::Pressed {
-                            let mut keysym_name = format!("{:?}", keysym).to_lowercase();
+                        // The overview owns the keyboard while it is shown, but leaves modified
+                        // keys to the bindings so the overview can be toggled off again.
+                        let plain = !(modifiers.ctrl || modifiers.alt || modifiers.logo);
+                        if state.overview.is_some() && plain && event.state() == KeyState::Pressed {
+                            let is_overview_key = matches!(
+                                keysym,
+                                Keysym::Escape
+                                    | Keysym::Return
+                                    | Keysym::KP_Enter
+                                    | Keysym::space
+                                    | Keysym::Left
+                                    | Keysym::Right
+                                    | Keysym::Up
+                                    | Keysym::Down
+                                    | Keysym::h
+                                    | Keysym::j
+                                    | Keysym::k
+                                    | Keysym::l
+                                    | Keysym::Tab
+                                    | Keysym::ISO_Left_Tab
+                            );
 
-                            if keysym_name.starts_with("xk_") {
-                                keysym_name = keysym_name[3..].to_string();
+                            if is_overview_key {
+                                return FilterResult::Intercept(Some(keysym));
                             }
+                        }
+
+                        // The release of a key that triggered a binding is not forwarded
+                        // either, the client never saw it being pressed.
+                        if event.state() == KeyState::Released
+                            && state.suppressed_keys.remove(&handle.raw_code())
+                        {
+                            return FilterResult::Intercept(None);
+                        }
 
-                            // DEBUG: keys
-                            tracing::info!("Key: '{}' | Mods: {:?}", keysym_name, modifiers);
+                        if event.state() == KeyState::Pressed {
+                            tracing::debug!("Key: {:?} | Mods: {:?}", keysym, modifiers);
 
                             // Emergency Exit
-                            if (modifiers.ctrl && modifiers.alt && keysym == Keysym::BackSpace)
-                                || keysym == Keysym::Escape
-                            {
+                            if modifiers.ctrl && modifiers.alt && keysym == Keysym::BackSpace {
                                 state.loop_signal.stop();
                             }
 
-                            for (name, binding) in &state.config.keybindings {
-                                let parts: Vec<&str> = binding.combo.split('+').collect();
-
-                                if let Some(key_part) = parts.last() {
-                                    let target_key: String = key_part.to_lowercase();
-
-                                    let is_key_match = keysym_name == target_key
-                                        || (target_key == "return"
-                                            && (keysym_name == "return"
-                                                || keysym_name == "kp_enter"));
-
-                                    if is_key_match {
-                                        let mut mods_match = true;
-
-                                        for &part in &parts[..parts.len() - 1] {
-                                            match part.to_lowercase().as_str() {
-                                                "ctrl" => mods_match &= modifiers.ctrl,
-                                                "alt" => mods_match &= modifiers.alt,
-                                                "shift" => mods_match &= modifiers.shift,
-                                                "logo" | "super" | "win" => {
-                                                    mods_match &= modifiers.logo
-                                                }
-                                                _ => {}
-                                            }
-                                        }
-
-                                        if mods_match {
-                                            if name == "quit" {
-                                                state.loop_signal.stop();
-                                            } else {
-                                                let _ = std::process::Command::new("sh")
-                                                    .arg("-c")
-                                                    .arg(&binding.command)
-                                                    .spawn();
-                                            }
-                                        }
-                                    }
-                                }
+                            let binding = state.bindings.find(&state.mode, modifiers, &handle);
+                            if let Some(binding) = binding {
+                                fired = Some((binding.action.clone(), binding.command.clone()));
+                                state.suppressed_keys.insert(handle.raw_code());
+                                return FilterResult::Intercept(None);
                             }
                         }
 
-                        FilterResult::Forward
+                        if state.overview.is_some() {
+                            FilterResult::Intercept(None)
+                        } else {
+                            FilterResult::Forward
+                        }
                     },
                 );
+
+                if let Some((action, command)) = fired {
+                    self.run_action(action, &command);
+                }
+
+                if let Some(Some(keysym)) = overview_key {
+                    self.handle_overview_key(keysym, serial);
+                }
             }
             InputEvent::PointerMotionAbsolute { event, .. } => {
-                let output = self.space.outputs().next().unwrap();
-                let output_geo = self.space.output_geometry(output).unwrap();
-                let pos = event.position_transformed(output_geo.size) + output_geo.loc.to_f64();
+                let output = self.space.outputs().next().unwrap().clone();
+                let output_geo = self.space.output_geometry(&output).unwrap();
+                let pos = self
+                    .viewport(&output)
+                    .to_canvas(event.position_transformed(output_geo.size));
 
                 let serial = SERIAL_COUNTER.next_serial();
                 let pointer = self.seat.get_pointer().unwrap();
 
-                let under = self.surface_under(pos);
+                let under = if self.overview.is_some() {
+                    self.overview_hover(pos);
+                    None
+                } else {
+                    self.surface_under(pos)
+                };
                 pointer.motion(
                     self,
                     under,
@@ -110,19 +150,134 @@ impl Clux {
                 );
                 pointer.frame(self);
             }
+            InputEvent::PointerMotion { event, .. } => {
+                let serial = SERIAL_COUNTER.next_serial();
+                let pointer = self.seat.get_pointer().unwrap();
+                let current = pointer.current_location();
+
+                // Deltas are in screen pixels, which cover less of the canvas when zoomed in.
+                let zoom = self
+                    .output_at(current)
+                    .map_or(1.0, |output| self.viewport(&output).zoom);
+                let pos = self.clamp_to_outputs(current, current + event.delta().downscale(zoom));
+
+                let under = if self.overview.is_some() {
+                    self.overview_hover(pos);
+                    None
+                } else {
+                    self.surface_under(pos)
+                };
+                pointer.motion(
+                    self,
+                    under.clone(),
+                    &MotionEvent {
+                        location: pos,
+                        serial,
+                        time: event.time_msec(),
+                    },
+                );
+                pointer.relative_motion(
+                    self,
+                    under,
+                    &RelativeMotionEvent {
+                        delta: event.delta(),
+                        delta_unaccel: event.delta_unaccel(),
+                        utime: event.time(),
+                    },
+                );
+                pointer.frame(self);
+            }
             InputEvent::PointerButton { event, .. } => {
                 let serial = SERIAL_COUNTER.next_serial();
                 let pointer = self.seat.get_pointer().unwrap();
-                let keyboard = self.seat.get_keyboard().unwrap();
 
-                if event.state() == smithay::backend::input::ButtonState::Pressed {
+                // Clicks on the overview pick a thumbnail and never reach the clients.
+                if self.overview.is_some() {
+                    if event.state() == ButtonState::Released {
+                        let pos = pointer.current_location();
+                        if let Some(window) = self.overview_window_under(pos) {
+                            self.overview.as_mut().unwrap().selected = Some(window);
+                            self.overview_confirm(serial);
+                        }
+                    }
+                    return;
+                }
+
+                if event.state() == ButtonState::Pressed {
                     let pos = pointer.current_location();
+                    let decoration = self.decoration_under(pos);
                     let window = self.space.element_under(pos).map(|(w, _)| w.clone());
+                    let mods = self.seat.get_keyboard().unwrap().modifier_state();
+
+                    // logo+middle-drag anywhere, or logo+left-drag on the background, pans
+                    // the canvas.
+                    let on_background = window.is_none() && decoration.is_none();
+                    let pans = mods.logo
+                        && (event.button_code() == BTN_MIDDLE
+                            || (event.button_code() == BTN_LEFT && on_background));
 
-                    if let Some(window) = window {
-                        self.space.raise_element(&window, true);
-                        if let Some(toplevel) = window.toplevel() {
-                            keyboard.set_focus(self, Some(toplevel.wl_surface().clone()), serial);
+                    if pans && !pointer.is_grabbed() {
+                        if let Some(output) = self.output_at(pos) {
+                            let grab = PanGrab {
+                                start_data: PointerGrabStartData {
+                                    focus: None,
+                                    button: event.button_code(),
+                                    location: pos,
+                                },
+                                output,
+                            };
+                            pointer.set_grab(self, grab, serial, Focus::Clear);
+                        }
+                    } else if let Some((window, part)) = decoration {
+                        self.focus_window(&window, serial);
+
+                        if event.button_code() == BTN_LEFT && !pointer.is_grabbed() {
+                            let start_data = PointerGrabStartData {
+                                focus: None,
+                                button: event.button_code(),
+                                location: pos,
+                            };
+                            match part {
+                                DecorationPart::Titlebar => {
+                                    self.start_move_grab(window, start_data, serial)
+                                }
+                                DecorationPart::Border(edges) => {
+                                    self.start_resize_grab(window, start_data, edges, serial)
+                                }
+                                button => self.pressed_decoration_button = Some((window, button)),
+                            }
+                        }
+                    } else if let Some(window) = window {
+                        self.focus_window(&window, serial);
+
+                        let modifiers = Modifiers::from_state(&mods);
+                        let drags = self
+                            .config
+                            .pointer
+                            .drag_modifier()
+                            .is_some_and(|modifier| modifiers.contains(modifier));
+                        if drags && !pointer.is_grabbed() {
+                            let start_data = PointerGrabStartData {
+                                focus: None,
+                                button: event.button_code(),
+                                location: pos,
+                            };
+                            match event.button_code() {
+                                BTN_LEFT => self.start_move_grab(window, start_data, serial),
+                                BTN_RIGHT => {
+                                    let edges = self.closest_corner(&window, pos);
+                                    self.start_resize_grab(window, start_data, edges, serial)
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                } else if event.button_code() == BTN_LEFT {
+                    // Titlebar buttons run on release, and only over the button pressed.
+                    if let Some((window, part)) = self.pressed_decoration_button.take() {
+                        let pos = pointer.current_location();
+                        if self.decoration_under(pos) == Some((window.clone(), part)) {
+                            self.click_decoration_button(&window, part);
                         }
                     }
                 }
@@ -138,7 +293,279 @@ impl Clux {
                 );
                 pointer.frame(self);
             }
+            InputEvent::PointerAxis { event, .. } => {
+                let source = event.source();
+
+                // Wheels may only report v120 steps, one notch being 120 and worth 15 pixels.
+                let amount = |axis| {
+                    event
+                        .amount(axis)
+                        .or_else(|| event.amount_v120(axis).map(|v120| v120 * 15.0 / 120.0))
+                        .unwrap_or(0.0)
+                };
+                let horizontal = amount(Axis::Horizontal);
+                let vertical = amount(Axis::Vertical);
+
+                let v120 = |axis, amount: f64| {
+                    event.amount_v120(axis).unwrap_or(amount * 120.0 / 15.0)
+                };
+                let steps = (v120(Axis::Horizontal, horizontal), v120(Axis::Vertical, vertical));
+                if let Some(fired) = self.scroll_bindings(steps) {
+                    for binding in fired {
+                        self.run_action(binding.action, &binding.command);
+                    }
+                    return;
+                }
+
+                let mut frame = AxisFrame::new(event.time_msec()).source(source);
+                for (axis, amount) in [(Axis::Horizontal, horizontal), (Axis::Vertical, vertical)] {
+                    if amount != 0.0 {
+                        frame = frame
+                            .relative_direction(axis, event.relative_direction(axis))
+                            .value(axis, amount);
+                        if let Some(v120) = event.amount_v120(axis) {
+                            frame = frame.v120(axis, v120 as i32);
+                        }
+                    }
+
+                    // Touchpads report a final zero to tell kinetic scrolling to stop.
+                    if source == AxisSource::Finger && event.amount(axis) == Some(0.0) {
+                        frame = frame.stop(axis);
+                    }
+                }
+
+                let pointer = self.seat.get_pointer().unwrap();
+                pointer.axis(self, frame);
+                pointer.frame(self);
+            }
+            InputEvent::GestureSwipeBegin { event, .. } => {
+                self.swipe_begin(event.fingers(), event.time_msec());
+            }
+            InputEvent::GestureSwipeUpdate { event, .. } => {
+                self.swipe_update(event.delta(), event.time_msec());
+            }
+            InputEvent::GestureSwipeEnd { event, .. } => {
+                self.swipe_end(event.cancelled(), event.time_msec());
+            }
+            InputEvent::GesturePinchBegin { event, .. } => {
+                self.pinch_begin(event.fingers(), event.time_msec());
+            }
+            InputEvent::GesturePinchUpdate { event, .. } => {
+                self.pinch_update(
+                    event.delta(),
+                    event.scale(),
+                    event.rotation(),
+                    event.time_msec(),
+                );
+            }
+            InputEvent::GesturePinchEnd { event, .. } => {
+                self.pinch_end(event.cancelled(), event.time_msec());
+            }
+            InputEvent::GestureHoldBegin { event, .. } => {
+                self.hold_begin(event.fingers(), event.time_msec());
+            }
+            InputEvent::GestureHoldEnd { event, .. } => {
+                self.hold_end(event.cancelled(), event.time_msec());
+            }
+            InputEvent::TouchDown { event, .. } => {
+                let Some(pos) = self.touch_location(&event) else {
+                    return;
+                };
+                let serial = SERIAL_COUNTER.next_serial();
+
+                // Like clicks, taps on the overview pick a thumbnail and never reach the clients.
+                if self.overview.is_some() {
+                    if let Some(window) = self.overview_window_under(pos) {
+                        self.overview.as_mut().unwrap().selected = Some(window);
+                        self.overview_confirm(serial);
+                    }
+                    return;
+                }
+
+                if let Some(window) = self.space.element_under(pos).map(|(w, _)| w.clone()) {
+                    self.focus_window(&window, serial);
+                }
+
+                let under = self.surface_under(pos);
+                let touch = self.seat.get_touch().unwrap();
+                touch.down(
+                    self,
+                    under,
+                    &DownEvent {
+                        slot: event.slot(),
+                        location: pos,
+                        serial,
+                        time: event.time_msec(),
+                    },
+                );
+            }
+            InputEvent::TouchMotion { event, .. } => {
+                let Some(pos) = self.touch_location(&event) else {
+                    return;
+                };
+
+                let under = self.surface_under(pos);
+                let touch = self.seat.get_touch().unwrap();
+                touch.motion(
+                    self,
+                    under,
+                    &TouchMotionEvent {
+                        slot: event.slot(),
+                        location: pos,
+                        time: event.time_msec(),
+                    },
+                );
+            }
+            InputEvent::TouchUp { event, .. } => {
+                let touch = self.seat.get_touch().unwrap();
+                touch.up(
+                    self,
+                    &UpEvent {
+                        slot: event.slot(),
+                        serial: SERIAL_COUNTER.next_serial(),
+                        time: event.time_msec(),
+                    },
+                );
+            }
+            InputEvent::TouchFrame { .. } => {
+                let touch = self.seat.get_touch().unwrap();
+                touch.frame(self);
+            }
+            InputEvent::TouchCancel { .. } => {
+                let touch = self.seat.get_touch().unwrap();
+                touch.cancel(self);
+            }
+            InputEvent::DeviceAdded { device } => self.add_tablet(&device),
+            InputEvent::DeviceRemoved { device } => self.remove_tablet(&device),
+            InputEvent::TabletToolProximity { event, .. } => self.tablet_proximity::<I>(event),
+            InputEvent::TabletToolAxis { event, .. } => self.tablet_axis::<I>(event),
+            InputEvent::TabletToolTip { event, .. } => self.tablet_tip::<I>(event),
+            InputEvent::TabletToolButton { event, .. } => self.tablet_button::<I>(event),
             _ => {}
         }
     }
+
+    /// Advances the scroll bindings bound for the held modifiers by `steps`, in v120 units.
+    ///
+    /// Returns the bindings to run, one per wheel notch worth of scrolling, or `None` when no
+    /// binding covers this scroll and it should go to the client instead.
+    fn scroll_bindings(&mut self, steps: (f64, f64)) -> Option<Vec<Binding>> {
+        let modifiers = self.seat.get_keyboard().unwrap().modifier_state();
+        let axes = [
+            (steps.0, ScrollDirection::Left, ScrollDirection::Right),
+            (steps.1, ScrollDirection::Up, ScrollDirection::Down),
+        ];
+
+        // Touchpads end a scroll with a frame of zeros, which belongs to whoever got the scroll.
+        let stop = steps == (0.0, 0.0);
+
+        let mut bound = false;
+        let mut fired = Vec::new();
+        for (idx, (delta, back, forward)) in axes.into_iter().enumerate() {
+            let accumulated = &mut self.scroll_accumulator[idx];
+            let find = |direction| self.bindings.find_scroll(&self.mode, &modifiers, direction);
+
+            if delta == 0.0 {
+                *accumulated = 0.0;
+                bound |= stop && (find(back).is_some() || find(forward).is_some());
+                continue;
+            }
+
+            let Some(binding) = find(if delta < 0.0 { back } else { forward }) else {
+                *accumulated = 0.0;
+                continue;
+            };
+            bound = true;
+
+            // Changing direction starts over instead of first undoing the previous scroll.
+            if accumulated.signum() != delta.signum() {
+                *accumulated = 0.0;
+            }
+            *accumulated += delta;
+            while accumulated.abs() >= SCROLL_STEP {
+                *accumulated -= SCROLL_STEP.copysign(delta);
+                fired.push(binding.clone());
+            }
+        }
+
+        bound.then_some(fired)
+    }
+
+    /// The output a device's absolute positions map to: the one it is mapped to in the config, or
+    /// the first one.
+    pub fn mapped_output(&self, device: &impl Device, kind: DeviceKind) -> Option<Output> {
+        let name = self.config.input.mapped_output(&device.name(), &[kind]);
+
+        name.and_then(|name| self.space.outputs().find(|o| o.name() == name))
+            .or_else(|| self.space.outputs().next())
+            .cloned()
+    }
+
+    /// Canvas position of a touch, on the output the touchscreen is mapped to.
+    fn touch_location<I: InputBackend>(
+        &self,
+        event: &impl AbsolutePositionEvent<I>,
+    ) -> Option<Point<f64, Logical>> {
+        let output = self.mapped_output(&event.device(), DeviceKind::Touch)?;
+        let size = self.space.output_geometry(&output)?.size;
+
+        Some(self.viewport(&output).to_canvas(event.position_transformed(size)))
+    }
+
+    fn start_move_grab(
+        &mut self,
+        window: Window,
+        start_data: PointerGrabStartData<Self>,
+        serial: Serial,
+    ) {
+        let Some(initial_window_location) = self.space.element_location(&window) else {
+            return;
+        };
+
+        let grab = MoveSurfaceGrab {
+            start_data,
+            window,
+            initial_window_location,
+        };
+        let pointer = self.seat.get_pointer().unwrap();
+        pointer.set_grab(self, grab, serial, Focus::Clear);
+    }
+
+    /// The corner of `window` closest to the canvas position `pos`.
+    fn closest_corner(&self, window: &Window, pos: Point<f64, Logical>) -> ResizeEdge {
+        let Some(geometry) = self.space.element_geometry(window) else {
+            return ResizeEdge::BOTTOM_RIGHT;
+        };
+
+        let center = geometry.to_f64().loc + geometry.size.to_f64().downscale(2.0).to_point();
+        let horizontal = if pos.x < center.x {
+            ResizeEdge::LEFT
+        } else {
+            ResizeEdge::RIGHT
+        };
+        let vertical = if pos.y < center.y {
+            ResizeEdge::TOP
+        } else {
+            ResizeEdge::BOTTOM
+        };
+
+        horizontal | vertical
+    }
+
+    /// Resizes `window` from `edges`.
+    fn start_resize_grab(
+        &mut self,
+        window: Window,
+        start_data: PointerGrabStartData<Self>,
+        edges: ResizeEdge,
+        serial: Serial,
+    ) {
+        let Some(geometry) = self.space.element_geometry(&window) else {
+            return;
+        };
+
+        let grab = ResizeSurfaceGrab::start(start_data, window, edges, geometry);
+        let pointer = self.seat.get_pointer().unwrap();
+        pointer.set_grab(self, grab, serial, Focus::Clear);
+    }
 }
@@ -0,0 +1,326 @@
+//! Control socket for scripts, bars and test automation.
+//!
+//! The socket lives next to the Wayland socket and its path is passed to spawned clients as
+//! `CLUX_SOCKET`. See [`protocol`] for the messages.
+
+pub mod protocol;
+
+use std::{
+    cell::RefCell,
+    ffi::OsStr,
+    io::{self, Read, Write},
+    net::Shutdown,
+    os::unix::net::{UnixListener, UnixStream},
+    path::PathBuf,
+    rc::Rc,
+};
+
+use smithay::{
+    reexports::calloop::{
+        generic::Generic, Interest, LoopHandle, Mode, PostAction, RegistrationToken,
+    },
+    utils::SERIAL_COUNTER,
+};
+
+use crate::{
+    window::{app_id_and_title, WindowId},
+    Clux,
+};
+use protocol::{OutputInfo, Reply, Request, Response, WindowInfo};
+
+/// Longest request line a client may send; clients going over it are dropped.
+const MAX_REQUEST_LEN: usize = 64 * 1024;
+
+/// How many reply bytes may wait for a client that does not read them before it is dropped.
+const MAX_QUEUED_REPLIES: usize = 4 * 1024 * 1024;
+
+pub struct IpcServer {
+    pub path: PathBuf,
+}
+
+impl IpcServer {
+    pub fn start(loop_handle: &LoopHandle<'static, Clux>, socket_name: &OsStr) -> io::Result<Self> {
+        let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(std::env::temp_dir);
+        let path = runtime_dir.join(format!("clux.{}.sock", socket_name.to_string_lossy()));
+
+        // A previous instance that crashed may have left its socket behind.
+        let _ = std::fs::remove_file(&path);
+
+        let listener = UnixListener::bind(&path)?;
+        listener.set_nonblocking(true)?;
+
+        let handle = loop_handle.clone();
+        loop_handle
+            .insert_source(
+                Generic::new(listener, Interest::READ, Mode::Level),
+                move |_, listener, _| {
+                    loop {
+                        match listener.accept() {
+                            Ok((stream, _)) => {
+                                if let Err(err) = accept_client(&handle, stream) {
+                                    tracing::warn!("Failed to accept IPC client: {}", err);
+                                }
+                            }
+                            Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                            Err(err) => {
+                                tracing::warn!("IPC socket error: {}", err);
+                                break;
+                            }
+                        }
+                    }
+                    Ok(PostAction::Continue)
+                },
+            )
+            .map_err(|err| io::Error::other(err.error.to_string()))?;
+
+        Ok(Self { path })
+    }
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// One connected IPC client, shared between the sources reading from and writing to it.
+struct Client {
+    stream: UnixStream,
+    /// Bytes of a request line that has not been terminated yet.
+    incoming: Vec<u8>,
+    /// Replies the socket has not taken yet.
+    outgoing: Vec<u8>,
+    /// The source flushing `outgoing`, registered only while there is something left to flush.
+    writer: Option<RegistrationToken>,
+}
+
+impl Client {
+    /// Writes as much of `outgoing` as the socket takes without blocking.
+    fn write_out(&mut self) -> io::Result<()> {
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(n) => {
+                    self.outgoing.drain(..n);
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
+    /// Hangs up on the client, dropping whatever was still queued for it.
+    fn close(&mut self, loop_handle: &LoopHandle<'static, Clux>) {
+        if let Some(token) = self.writer.take() {
+            loop_handle.remove(token);
+        }
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+fn accept_client(loop_handle: &LoopHandle<'static, Clux>, stream: UnixStream) -> io::Result<()> {
+    // Nothing here may block the event loop: replies the socket cannot take right away are
+    // queued and flushed once it becomes writable again.
+    stream.set_nonblocking(true)?;
+
+    let client = Rc::new(RefCell::new(Client {
+        stream: stream.try_clone()?,
+        incoming: Vec::new(),
+        outgoing: Vec::new(),
+        writer: None,
+    }));
+
+    let handle = loop_handle.clone();
+    loop_handle
+        .insert_source(
+            Generic::new(stream, Interest::READ, Mode::Level),
+            move |_, stream, state| {
+                {
+                    let mut client = client.borrow_mut();
+                    let mut buf = [0u8; 4096];
+                    match (&**stream).read(&mut buf) {
+                        // Replies to what the client sent before hanging up its end are still
+                        // flushed by the writer.
+                        Ok(0) => return Ok(PostAction::Remove),
+                        Ok(n) => client.incoming.extend_from_slice(&buf[..n]),
+                        Err(err) if err.kind() == io::ErrorKind::WouldBlock => {}
+                        Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                        Err(_) => {
+                            client.close(&handle);
+                            return Ok(PostAction::Remove);
+                        }
+                    }
+
+                    while let Some(end) = client.incoming.iter().position(|b| *b == b'\n') {
+                        let line: Vec<u8> = client.incoming.drain(..=end).collect();
+                        let response = match serde_json::from_slice::<Request>(&line) {
+                            Ok(request) => state.handle_ipc_request(request),
+                            Err(err) => Response::Error(format!("invalid request: {err}")),
+                        };
+
+                        serde_json::to_writer(&mut client.outgoing, &response).unwrap();
+                        client.outgoing.push(b'\n');
+                    }
+
+                    if client.incoming.len() > MAX_REQUEST_LEN {
+                        tracing::warn!(
+                            "Dropping IPC client that sent a request longer than {} bytes",
+                            MAX_REQUEST_LEN
+                        );
+                        client.close(&handle);
+                        return Ok(PostAction::Remove);
+                    }
+                    if client.outgoing.len() > MAX_QUEUED_REPLIES {
+                        tracing::warn!("Dropping IPC client that does not read its replies");
+                        client.close(&handle);
+                        return Ok(PostAction::Remove);
+                    }
+                }
+
+                if let Err(err) = flush(&client, &handle) {
+                    tracing::warn!("Failed to reply to IPC client: {}", err);
+                    client.borrow_mut().close(&handle);
+                    return Ok(PostAction::Remove);
+                }
+                Ok(PostAction::Continue)
+            },
+        )
+        .map_err(|err| io::Error::other(err.error.to_string()))?;
+
+    Ok(())
+}
+
+/// Writes out the replies queued for `client`, leaving a source behind to write the rest once
+/// the socket is writable again if it does not take them all.
+fn flush(client: &Rc<RefCell<Client>>, loop_handle: &LoopHandle<'static, Clux>) -> io::Result<()> {
+    let mut inner = client.borrow_mut();
+    inner.write_out()?;
+    if inner.outgoing.is_empty() || inner.writer.is_some() {
+        return Ok(());
+    }
+
+    let writable = Generic::new(inner.stream.try_clone()?, Interest::WRITE, Mode::Level);
+    let client = client.clone();
+    let token = loop_handle
+        .insert_source(writable, move |_, _, _| {
+            let mut client = client.borrow_mut();
+            if let Err(err) = client.write_out() {
+                tracing::warn!("Failed to reply to IPC client: {}", err);
+                client.outgoing.clear();
+                let _ = client.stream.shutdown(Shutdown::Both);
+            }
+            if client.outgoing.is_empty() {
+                client.writer = None;
+                return Ok(PostAction::Remove);
+            }
+            Ok(PostAction::Continue)
+        })
+        .map_err(|err| io::Error::other(err.error.to_string()))?;
+    inner.writer = Some(token);
+
+    Ok(())
+}
+
+impl Clux {
+    pub fn handle_ipc_request(&mut self, request: Request) -> Response {
+        match request {
+            Request::Outputs => Response::Ok(Reply::Outputs(self.output_infos())),
+            Request::Windows => Response::Ok(Reply::Windows(self.window_infos())),
+            Request::Mode => Response::Ok(Reply::Mode(self.mode.clone())),
+            Request::Layout => Response::Ok(Reply::Layout(self.active_layout())),
+            Request::Focus { id } => {
+                let Some(window) = self.window_by_id(WindowId(id)) else {
+                    return no_such_window(id);
+                };
+                self.focus_window(&window, SERIAL_COUNTER.next_serial());
+                Response::Ok(Reply::Handled)
+            }
+            Request::Move { id, x, y } => {
+                let Some(window) = self.window_by_id(WindowId(id)) else {
+                    return no_such_window(id);
+                };
+                self.space.map_element(window, (x, y), false);
+                Response::Ok(Reply::Handled)
+            }
+            Request::Close { id } => {
+                let Some(window) = self.window_by_id(WindowId(id)) else {
+                    return no_such_window(id);
+                };
+                if let Some(toplevel) = window.toplevel() {
+                    toplevel.send_close();
+                }
+                Response::Ok(Reply::Handled)
+            }
+            Request::Spawn { command } => match self.spawn(&command) {
+                Ok(()) => Response::Ok(Reply::Handled),
+                Err(err) => Response::Error(format!("failed to spawn `{command}`: {err}")),
+            },
+            Request::Reload => match self.reload_config() {
+                Ok(()) => Response::Ok(Reply::Handled),
+                Err(err) => Response::Error(format!("{err:#}")),
+            },
+            Request::Quit => {
+                self.loop_signal.stop();
+                Response::Ok(Reply::Handled)
+            }
+        }
+    }
+
+    fn output_infos(&self) -> Vec<OutputInfo> {
+        self.space
+            .outputs()
+            .map(|output| {
+                let viewport = self.viewport(output);
+                let size = self
+                    .space
+                    .output_geometry(output)
+                    .map(|geo| geo.size)
+                    .unwrap_or_default();
+                let properties = output.physical_properties();
+
+                OutputInfo {
+                    name: output.name(),
+                    make: properties.make,
+                    model: properties.model,
+                    x: viewport.origin.x.round() as i32,
+                    y: viewport.origin.y.round() as i32,
+                    width: size.w,
+                    height: size.h,
+                    scale: output.current_scale().fractional_scale(),
+                    zoom: viewport.zoom,
+                }
+            })
+            .collect()
+    }
+
+    fn window_infos(&self) -> Vec<WindowInfo> {
+        let focused = self.focused_window();
+
+        self.space
+            .elements()
+            .map(|window| {
+                let (app_id, title) = app_id_and_title(window);
+                let geo = self.space.element_geometry(window).unwrap_or_default();
+
+                WindowInfo {
+                    id: WindowId::of(window).0,
+                    app_id,
+                    title,
+                    x: geo.loc.x,
+                    y: geo.loc.y,
+                    width: geo.size.w,
+                    height: geo.size.h,
+                    focused: focused.as_ref() == Some(window),
+                }
+            })
+            .collect()
+    }
+}
+
+fn no_such_window(id: u64) -> Response {
+    Response::Error(format!("no window with id {id}"))
+}
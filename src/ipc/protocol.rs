@@ -0,0 +1,79 @@
+//! Messages exchanged over the control socket.
+//!
+//! Every request is a single line of JSON, eg. `{"request":"focus","id":3}`, and is answered with
+//! a single line of JSON, either `{"ok":...}` or `{"error":"..."}`.
+
+use serde::{Deserialize, Serialize};
+
+/// Environment variable holding the path of the control socket.
+pub const SOCKET_ENV: &str = "CLUX_SOCKET";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "request", rename_all = "snake_case")]
+pub enum Request {
+    Outputs,
+    Windows,
+    /// The active binding mode.
+    Mode,
+    /// The active keyboard layout.
+    Layout,
+    Focus { id: u64 },
+    Move { id: u64, x: i32, y: i32 },
+    Close { id: u64 },
+    Spawn { command: String },
+    Reload,
+    Quit,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Response {
+    Ok(Reply),
+    Error(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reply {
+    Handled,
+    Outputs(Vec<OutputInfo>),
+    Windows(Vec<WindowInfo>),
+    Mode(String),
+    Layout(LayoutInfo),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputInfo {
+    pub name: String,
+    pub make: String,
+    pub model: String,
+    /// Canvas position shown at the top-left corner of the output.
+    pub x: i32,
+    pub y: i32,
+    /// Logical size of the output.
+    pub width: i32,
+    pub height: i32,
+    pub scale: f64,
+    pub zoom: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayoutInfo {
+    /// Position of the layout in the `[keyboard]` list.
+    pub index: u32,
+    /// Full xkb name, eg. `English (US)`.
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowInfo {
+    pub id: u64,
+    pub app_id: Option<String>,
+    pub title: Option<String>,
+    /// Canvas position of the window geometry.
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    pub focused: bool,
+}
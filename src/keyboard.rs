@@ -0,0 +1,261 @@
+//! Keyboard layouts and key repeat, as configured in `[keyboard]`.
+//!
+//! All keyboards share the seat's keymap. Keyboards with an entry of their own in
+//! `[[keyboard.devices]]` get theirs installed when they are typed on, and the keymap of
+//! `[keyboard]` comes back once another one is.
+//!
+//! Keymaps are compiled when the config is loaded, a keyboard switch only installs the one kept
+//! for it.
+//!
+//! With `layout_tracking = "window"`, each window remembers the layout it was left with and gets
+//! it back when focused again.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use anyhow::Context;
+use smithay::{
+    input::keyboard::{xkb, Layout},
+    reexports::wayland_server::protocol::wl_surface::WlSurface,
+};
+
+use crate::{
+    config::{KeyboardConfig, LayoutTracking},
+    ipc::protocol::LayoutInfo,
+    window::WindowId,
+    Clux,
+};
+
+/// Layout a window had when it last lost the keyboard focus.
+#[derive(Default)]
+struct WindowLayout(AtomicU32);
+
+/// Keymaps of `[keyboard]` and of its `[[keyboard.devices]]` entries, in that order, as the text
+/// smithay installs. Entries without one leave the keyboard's keymap alone.
+#[derive(Default)]
+pub struct Keymaps(Vec<Option<String>>);
+
+impl Keymaps {
+    /// Compiles every keymap of `config`, failing on the first one xkb rejects.
+    pub fn compile(config: &KeyboardConfig) -> anyhow::Result<Self> {
+        let keymaps = compile_each(config).collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self(keymaps.into_iter().map(Some).collect()))
+    }
+
+    /// Compiles every keymap of `config`, logging the ones xkb rejects and going without them.
+    pub fn compile_lenient(config: &KeyboardConfig) -> Self {
+        let keymaps = compile_each(config).map(|keymap| {
+            keymap
+                .map_err(|err| tracing::error!("{:#}, keeping the keymap in use instead", err))
+                .ok()
+        });
+        Self(keymaps.collect())
+    }
+
+    /// The keymap of the `[[keyboard.devices]]` entry at `device`, or of `[keyboard]` for `None`.
+    pub fn get(&self, device: Option<usize>) -> Option<&str> {
+        let idx = device.map_or(0, |idx| idx + 1);
+        self.0.get(idx)?.as_deref()
+    }
+}
+
+/// Compiles the keymaps of `[keyboard]` and of its `[[keyboard.devices]]` entries, in that order.
+fn compile_each(config: &KeyboardConfig) -> impl Iterator<Item = anyhow::Result<String>> + '_ {
+    let devices = (0..config.devices.len()).map(Some);
+    std::iter::once(None).chain(devices).map(|device| {
+        let keymap = compile_keymap(&config.keymap(device))?;
+        Ok(keymap.get_as_string(xkb::KEYMAP_FORMAT_TEXT_V1))
+    })
+}
+
+/// Compiles the keymap `config` describes, from its keymap file if it has one.
+pub fn compile_keymap(config: &KeyboardConfig) -> anyhow::Result<xkb::Keymap> {
+    match &config.keymap_file {
+        Some(path) => read_keymap_file(path),
+        None => compile_layout(config),
+    }
+}
+
+/// Compiles the layouts, variants and options of `config`.
+pub fn compile_layout(config: &KeyboardConfig) -> anyhow::Result<xkb::Keymap> {
+    let context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
+    let keymap = xkb::Keymap::new_from_names(
+        &context,
+        "",
+        "",
+        &config.layout,
+        &config.variant,
+        config.options.clone(),
+        xkb::KEYMAP_COMPILE_NO_FLAGS,
+    );
+
+    keymap.with_context(|| {
+        format!(
+            "xkb could not compile layout `{}` with variant `{}` and options `{}`",
+            config.layout,
+            config.variant,
+            config.options.as_deref().unwrap_or("")
+        )
+    })
+}
+
+/// Reads and compiles the `.xkb` keymap at `path`.
+pub fn read_keymap_file(path: &Path) -> anyhow::Result<xkb::Keymap> {
+    let path = expand_home(path);
+    let keymap = std::fs::read_to_string(&path)
+        .with_context(|| format!("failed to read keymap file {}", path.display()))?;
+
+    let context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
+    let compiled = xkb::Keymap::new_from_string(
+        &context,
+        keymap,
+        xkb::KEYMAP_FORMAT_TEXT_V1,
+        xkb::KEYMAP_COMPILE_NO_FLAGS,
+    );
+
+    compiled.with_context(|| format!("xkb could not compile keymap file {}", path.display()))
+}
+
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+impl Clux {
+    /// Applies `[keyboard]` again after it and its keymaps replaced `previous` and
+    /// `previous_keymaps`.
+    pub fn apply_keyboard_config(&mut self, previous: &KeyboardConfig, previous_keymaps: &Keymaps) {
+        let config = self.config.keyboard.clone();
+
+        let before = previous_keymaps.get(self.keymap_device);
+        // Entries may have moved, the next key press finds its keyboard's again.
+        if config.devices != previous.devices {
+            self.keymap_device = None;
+        }
+
+        // Compared as compiled, a keymap file may have changed even though the config did not.
+        if self.keymaps.get(self.keymap_device) != before {
+            self.apply_keymap();
+        }
+        if (config.repeat_delay, config.repeat_rate)
+            != (previous.repeat_delay, previous.repeat_rate)
+        {
+            let keyboard = self.seat.get_keyboard().unwrap();
+            keyboard.change_repeat_info(config.repeat_rate as i32, config.repeat_delay as i32);
+        }
+    }
+
+    /// Installs the keymap of the keyboard called `device` if another one is in use, so that its
+    /// key presses get the right layout. The active layout and the modifiers, which may have been
+    /// set on the other keyboard, carry over.
+    pub fn switch_keymap_for(&mut self, device: &str) {
+        let keymap_device = self.config.keyboard.device_index(device);
+        if keymap_device == self.keymap_device {
+            return;
+        }
+        let same_keymap = self.keymaps.get(keymap_device) == self.keymaps.get(self.keymap_device);
+        self.keymap_device = keymap_device;
+        if same_keymap {
+            return;
+        }
+
+        let keyboard = self.seat.get_keyboard().unwrap();
+        let modifiers = keyboard.modifier_state();
+        let layout = self.active_layout().index;
+
+        self.apply_keymap();
+
+        keyboard.set_modifier_state(modifiers);
+        // Setting the layout also sends clients the modifiers restored above.
+        keyboard.with_xkb_state(self, |mut context| context.set_layout(Layout(layout)));
+    }
+
+    /// Installs the keymap compiled for the keyboard last typed on, keeping the current one if
+    /// there is none. Clients are sent the new keymap right away.
+    pub fn apply_keymap(&mut self) {
+        let Some(keymap) = self.keymaps.get(self.keymap_device) else {
+            return;
+        };
+        let keymap = keymap.to_string();
+
+        let keyboard = self.seat.get_keyboard().unwrap();
+        if let Err(err) = keyboard.set_keymap_from_string(self, keymap) {
+            tracing::error!(
+                "failed to install the keymap: {:?}, keeping the previous one",
+                err
+            );
+        }
+    }
+
+    /// Switches to the next configured layout, going back to the first after the last.
+    pub fn cycle_layout(&mut self) {
+        let keyboard = self.seat.get_keyboard().unwrap();
+        keyboard.with_xkb_state(self, |mut context| context.cycle_next_layout());
+    }
+
+    /// The layout in use, which xkb options such as `grp:alt_shift_toggle` may switch too.
+    pub fn active_layout(&mut self) -> LayoutInfo {
+        let keyboard = self.seat.get_keyboard().unwrap();
+        keyboard.with_xkb_state(self, |context| {
+            let xkb = context.xkb().lock().unwrap();
+            let Layout(index) = xkb.active_layout();
+
+            LayoutInfo {
+                index,
+                name: xkb.layout_name(Layout(index)).to_string(),
+            }
+        })
+    }
+
+    /// With per-window layouts, leaves the layout with the window losing the keyboard focus and
+    /// gives the keyboard the one of the window owning `focused`. Done once the focus change is
+    /// over, the keyboard is busy with it until then.
+    pub fn queue_window_layout(&mut self, focused: Option<&WlSurface>) {
+        if self.config.keyboard.layout_tracking != LayoutTracking::Window {
+            return;
+        }
+        // Layer surfaces and no focus at all own no layout, whatever is picked meanwhile stays
+        // for the next window.
+        let id = focused
+            .and_then(|surface| self.window_for_surface(surface))
+            .map(|window| WindowId::of(&window));
+        self.loop_handle
+            .insert_idle(move |state| state.switch_window_layout(id));
+    }
+
+    /// Remembers the layout of the window that had the focus so far, then switches to the one
+    /// `focused` was left with.
+    fn switch_window_layout(&mut self, focused: Option<WindowId>) {
+        let current = self.active_layout().index;
+
+        if let Some(previous) = self
+            .layout_window
+            .take()
+            .and_then(|id| self.window_by_id(id))
+        {
+            previous
+                .user_data()
+                .insert_if_missing_threadsafe(WindowLayout::default);
+            let layout = previous.user_data().get::<WindowLayout>().unwrap();
+            layout.0.store(current, Ordering::Relaxed);
+        }
+
+        let Some(window) = focused.and_then(|id| self.window_by_id(id)) else {
+            return;
+        };
+        self.layout_window = focused;
+
+        let restored = window
+            .user_data()
+            .get::<WindowLayout>()
+            .map_or(0, |layout| layout.0.load(Ordering::Relaxed));
+        if restored != current {
+            let keyboard = self.seat.get_keyboard().unwrap();
+            keyboard.with_xkb_state(self, |mut context| context.set_layout(Layout(restored)));
+        }
+    }
+}
@@ -1,22 +1,41 @@
+mod actions;
 mod backend;
+mod bindings;
+mod canvas;
+mod check;
 mod config;
+mod cursor;
+mod decorations;
+mod devices;
+mod gestures;
 mod grabs;
 mod handlers;
 mod input;
+mod keyboard;
+mod ipc;
+mod overview;
+mod reload;
+mod render;
 mod state;
+mod tablet;
+mod window;
+
+#[cfg(test)]
+mod tests;
 
 pub use state::Clux;
 
 static POSSIBLE_BACKENDS: &[&str] = &[
     "--winit : Run clux as a X11 or Wayland client using winit.",
     "--tty-udev : Run clux on a tty using udev.",
+    "--headless : Run clux without a display, rendering in software into memory.",
 ];
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     init_logging();
 
-    let arg = std::env::args().nth(1);
-    match arg.as_deref() {
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
         Some("--winit") => {
             tracing::info!("Starting clux with winit backend");
             backend::run_winit()?;
@@ -25,8 +44,23 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             tracing::info!("Starting clux on a tty using udev");
             backend::run_udev()?;
         }
+        Some("--headless") => {
+            tracing::info!("Starting clux headless");
+            backend::run_headless()?;
+        }
+        Some("--check-config") => {
+            let path = args
+                .next()
+                .map(std::path::PathBuf::from)
+                .unwrap_or_else(config::config_path);
+
+            if !check::check_config(&path) {
+                std::process::exit(1);
+            }
+        }
         _ => {
             println!("USAGE: clux --backend");
+            println!("       clux --check-config [path]");
             println!();
             println!("Possible backends are:");
             for b in POSSIBLE_BACKENDS {
@@ -0,0 +1,278 @@
+//! Spatial overview of the whole space.
+//!
+//! While the overview is active every window is drawn as a scaled thumbnail that keeps its
+//! position relative to the other windows, and input goes to the overview instead of clients.
+
+use smithay::{
+    backend::renderer::{
+        element::{solid::SolidColorRenderElement, AsRenderElements, Id, Kind},
+        utils::CommitCounter,
+        ImportAll, ImportMem, Renderer,
+    },
+    desktop::Window,
+    input::keyboard::Keysym,
+    output::Output,
+    utils::{Logical, Point, Rectangle, Serial, Size},
+};
+
+use crate::{render::CluxRenderElement, Clux};
+
+/// Gap kept between the thumbnails and the edges of the output.
+const PADDING: f64 = 48.0;
+/// Width of the frame drawn around the selected thumbnail.
+const HIGHLIGHT_WIDTH: f64 = 4.0;
+const HIGHLIGHT_COLOR: [f32; 4] = [0.33, 0.55, 0.85, 1.0];
+
+pub struct Overview {
+    /// Window that gets focused when the overview is confirmed.
+    pub selected: Option<Window>,
+    highlight_id: Id,
+}
+
+pub struct Thumbnail {
+    pub window: Window,
+    /// Where the window geometry is drawn, in output-local logical coordinates.
+    pub geometry: Rectangle<f64, Logical>,
+    pub scale: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Clux {
+    pub fn toggle_overview(&mut self) {
+        if self.overview.is_some() {
+            self.close_overview();
+        } else {
+            self.open_overview();
+        }
+    }
+
+    pub fn open_overview(&mut self) {
+        let selected = self
+            .focused_window()
+            .or_else(|| self.space.elements().last().cloned());
+
+        self.overview = Some(Overview {
+            selected,
+            highlight_id: Id::new(),
+        });
+    }
+
+    pub fn close_overview(&mut self) {
+        self.overview = None;
+    }
+
+    /// Lays out every window of the space so that all of them fit on `output`.
+    ///
+    /// Thumbnails are returned bottom to top, in the same order as `Space::elements`.
+    pub fn overview_layout(&self, output: &Output) -> Vec<Thumbnail> {
+        let Some(output_size) = self.space.output_geometry(output).map(|geo| geo.size) else {
+            return Vec::new();
+        };
+
+        let windows: Vec<_> = self
+            .space
+            .elements()
+            .filter_map(|w| Some((w.clone(), self.space.element_geometry(w)?)))
+            .collect();
+
+        let Some(bbox) = windows.iter().map(|(_, geo)| *geo).reduce(|a, b| a.merge(b)) else {
+            return Vec::new();
+        };
+        let bbox = bbox.to_f64();
+
+        let area_w = (output_size.w as f64 - 2.0 * PADDING).max(1.0);
+        let area_h = (output_size.h as f64 - 2.0 * PADDING).max(1.0);
+
+        let scale = (area_w / bbox.size.w).min(area_h / bbox.size.h).min(1.0);
+
+        // Center the scaled bounding box inside the padded output area.
+        let origin = Point::<f64, Logical>::from((
+            PADDING + (area_w - bbox.size.w * scale) / 2.0,
+            PADDING + (area_h - bbox.size.h * scale) / 2.0,
+        ));
+
+        windows
+            .into_iter()
+            .map(|(window, geo)| {
+                let geo = geo.to_f64();
+                let loc = origin + (geo.loc - bbox.loc).upscale(scale);
+                let size = Size::from((geo.size.w * scale, geo.size.h * scale));
+
+                Thumbnail {
+                    window,
+                    geometry: Rectangle::new(loc, size),
+                    scale,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the window whose thumbnail is under the canvas position `pos`.
+    pub fn overview_window_under(&self, pos: Point<f64, Logical>) -> Option<Window> {
+        let output = self.output_at(pos)?;
+        let local = self.viewport(&output).to_local(pos);
+
+        self.overview_layout(&output)
+            .into_iter()
+            .rev()
+            .find(|thumbnail| thumbnail.geometry.contains(local))
+            .map(|thumbnail| thumbnail.window)
+    }
+
+    /// Moves the selection to whatever thumbnail is under the pointer.
+    pub fn overview_hover(&mut self, pos: Point<f64, Logical>) {
+        let window = self.overview_window_under(pos);
+
+        if let (Some(overview), Some(window)) = (self.overview.as_mut(), window) {
+            overview.selected = Some(window);
+        }
+    }
+
+    /// Focuses and raises the selected window, then leaves the overview.
+    pub fn overview_confirm(&mut self, serial: Serial) {
+        let selected = self.overview.take().and_then(|overview| overview.selected);
+
+        if let Some(window) = selected {
+            self.focus_window(&window, serial);
+        }
+    }
+
+    /// Selects the closest window in `direction` from the current selection.
+    pub fn overview_select(&mut self, direction: Direction) {
+        let Some(overview) = self.overview.as_ref() else {
+            return;
+        };
+
+        let center = |window: &Window| {
+            self.space.element_geometry(window).map(|geo| {
+                let geo = geo.to_f64();
+                geo.loc + geo.size.downscale(2.0).to_point()
+            })
+        };
+
+        let Some(from) = overview.selected.as_ref().and_then(center) else {
+            let first = self.space.elements().last().cloned();
+            self.overview.as_mut().unwrap().selected = first;
+            return;
+        };
+
+        let best = self
+            .space
+            .elements()
+            .filter(|w| Some(*w) != overview.selected.as_ref())
+            .filter_map(|w| {
+                let delta = center(w)? - from;
+                let (along, across) = match direction {
+                    Direction::Left => (-delta.x, delta.y),
+                    Direction::Right => (delta.x, delta.y),
+                    Direction::Up => (-delta.y, delta.x),
+                    Direction::Down => (delta.y, delta.x),
+                };
+
+                // Prefer windows straight ahead over ones that are merely closer.
+                (along > 0.0).then(|| (w.clone(), along + across.abs() * 2.0))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(w, _)| w);
+
+        if let Some(window) = best {
+            self.overview.as_mut().unwrap().selected = Some(window);
+        }
+    }
+
+    /// Cycles the selection through the windows in stacking order.
+    pub fn overview_select_next(&mut self, forward: bool) {
+        let windows: Vec<_> = self.space.elements().cloned().collect();
+        let Some(overview) = self.overview.as_mut() else {
+            return;
+        };
+        if windows.is_empty() {
+            return;
+        }
+
+        let current = overview
+            .selected
+            .as_ref()
+            .and_then(|selected| windows.iter().position(|w| w == selected));
+
+        let next = match (current, forward) {
+            (Some(i), true) => (i + 1) % windows.len(),
+            (Some(i), false) => (i + windows.len() - 1) % windows.len(),
+            (None, _) => windows.len() - 1,
+        };
+
+        overview.selected = Some(windows[next].clone());
+    }
+
+    /// Handles a key press while the overview is active.
+    pub fn handle_overview_key(&mut self, keysym: Keysym, serial: Serial) {
+        match keysym {
+            Keysym::Escape => self.close_overview(),
+            Keysym::Return | Keysym::KP_Enter | Keysym::space => self.overview_confirm(serial),
+            Keysym::Left | Keysym::h => self.overview_select(Direction::Left),
+            Keysym::Right | Keysym::l => self.overview_select(Direction::Right),
+            Keysym::Up | Keysym::k => self.overview_select(Direction::Up),
+            Keysym::Down | Keysym::j => self.overview_select(Direction::Down),
+            Keysym::Tab => self.overview_select_next(true),
+            Keysym::ISO_Left_Tab => self.overview_select_next(false),
+            _ => {}
+        }
+    }
+
+    pub fn overview_render_elements<R>(
+        &self,
+        renderer: &mut R,
+        output: &Output,
+    ) -> Vec<CluxRenderElement<R>>
+    where
+        R: Renderer + ImportAll + ImportMem,
+        R::TextureId: Clone + 'static,
+    {
+        let Some(overview) = self.overview.as_ref() else {
+            return Vec::new();
+        };
+        let output_scale = output.current_scale().fractional_scale();
+
+        let mut elements = Vec::new();
+
+        for thumbnail in self.overview_layout(output).into_iter().rev() {
+            let geometry = thumbnail.geometry;
+            let location =
+                geometry.loc - thumbnail.window.geometry().loc.to_f64().upscale(thumbnail.scale);
+
+            elements.extend(thumbnail.window.render_elements::<CluxRenderElement<R>>(
+                renderer,
+                location.to_physical_precise_round(output_scale),
+                (output_scale * thumbnail.scale).into(),
+                1.0,
+            ));
+
+            if overview.selected.as_ref() == Some(&thumbnail.window) {
+                let frame = Rectangle::new(
+                    geometry.loc - Point::from((HIGHLIGHT_WIDTH, HIGHLIGHT_WIDTH)),
+                    geometry.size + Size::from((HIGHLIGHT_WIDTH * 2.0, HIGHLIGHT_WIDTH * 2.0)),
+                );
+
+                elements.push(
+                    SolidColorRenderElement::new(
+                        overview.highlight_id.clone(),
+                        frame.to_physical_precise_round(output_scale),
+                        CommitCounter::default(),
+                        HIGHLIGHT_COLOR,
+                        Kind::Unspecified,
+                    )
+                    .into(),
+                );
+            }
+        }
+
+        elements
+    }
+}
@@ -0,0 +1,143 @@
+//! Live config reloading.
+//!
+//! The config directory is watched with inotify, and whenever `config.toml` is written or
+//! replaced the file is parsed again and applied to the running compositor. A config that fails
+//! to parse is reported and the previous one stays active.
+
+use std::{io, path::Path};
+
+use inotify::{Inotify, WatchMask};
+use smithay::{
+    output::{Output, Scale},
+    reexports::calloop::{generic::Generic, Interest, LoopHandle, Mode, PostAction},
+    utils::Point,
+};
+
+use crate::{
+    actions::DEFAULT_MODE,
+    bindings::BindingTable,
+    config::{self, CluxConfig, OutputConfig},
+    keyboard::Keymaps,
+    Clux,
+};
+
+/// Reloads the config whenever the file at `path` changes.
+pub fn watch_config(loop_handle: &LoopHandle<'static, Clux>, path: &Path) -> io::Result<()> {
+    let (Some(dir), Some(file_name)) = (path.parent(), path.file_name()) else {
+        return Err(io::Error::other("config path has no parent directory"));
+    };
+    let file_name = file_name.to_owned();
+
+    // Editors often save by writing a new file and renaming it over the old one, which would
+    // drop a watch on the file itself, so watch the directory instead.
+    let inotify = Inotify::init()?;
+    inotify
+        .watches()
+        .add(dir, WatchMask::CLOSE_WRITE | WatchMask::MOVED_TO)?;
+
+    let path = path.to_owned();
+    loop_handle
+        .insert_source(
+            Generic::new(inotify, Interest::READ, Mode::Level),
+            move |_, inotify, state| {
+                let mut buffer = [0u8; 4096];
+                // SAFETY: the inotify instance is only read from, never replaced or closed.
+                let changed = match unsafe { inotify.get_mut() }.read_events(&mut buffer) {
+                    Ok(mut events) => events.any(|event| event.name == Some(file_name.as_os_str())),
+                    Err(err) if err.kind() == io::ErrorKind::WouldBlock => false,
+                    Err(err) => {
+                        tracing::warn!("Failed to read config watch events: {}", err);
+                        false
+                    }
+                };
+
+                if changed {
+                    match state.reload_config_from(&path) {
+                        Ok(()) => tracing::info!("Reloaded {}", path.display()),
+                        Err(err) => tracing::error!("{:#}, keeping the previous config", err),
+                    }
+                }
+
+                Ok(PostAction::Continue)
+            },
+        )
+        .map_err(|err| io::Error::other(err.error.to_string()))?;
+
+    Ok(())
+}
+
+impl Clux {
+    pub fn reload_config(&mut self) -> anyhow::Result<()> {
+        self.reload_config_from(&config::config_path())
+    }
+
+    /// Parses the config at `path` and applies it, leaving the current one active on errors.
+    pub fn reload_config_from(&mut self, path: &Path) -> anyhow::Result<()> {
+        let config = config::read_config(path)?;
+        // A broken keymap file fails the reload like a parse error, keeping the previous keymap.
+        let keymaps = Keymaps::compile(&config.keyboard)?;
+        self.apply_compiled_config(config, keymaps);
+        Ok(())
+    }
+
+    /// Applies `config` without the file reading of a reload. Keymaps that fail to compile are
+    /// logged and the keyboard keeps its keymap.
+    #[cfg(test)]
+    pub fn apply_config(&mut self, config: CluxConfig) {
+        let keymaps = Keymaps::compile_lenient(&config.keyboard);
+        self.apply_compiled_config(config, keymaps);
+    }
+
+    /// Replaces the active config and its compiled `keymaps`, and updates the keyboard, devices,
+    /// cursor and outputs to match.
+    fn apply_compiled_config(&mut self, config: CluxConfig, keymaps: Keymaps) {
+        self.bindings = BindingTable::new(&config);
+        if !self.bindings.has_mode(&self.mode) {
+            self.mode = DEFAULT_MODE.to_string();
+        }
+        let previous = std::mem::replace(&mut self.config, config);
+        let previous_keymaps = std::mem::replace(&mut self.keymaps, keymaps);
+
+        self.apply_keyboard_config(&previous.keyboard, &previous_keymaps);
+
+        if previous.input != self.config.input {
+            self.configure_input_devices();
+        }
+        if previous.cursor != self.config.cursor {
+            self.reload_cursors();
+        }
+
+        // Only touch outputs whose entry changed, so a reload keeps any panning done since.
+        let outputs: Vec<Output> = self.space.outputs().cloned().collect();
+        for output in outputs {
+            let before = find_output_config(&previous.outputs, &output);
+            if before != find_output_config(&self.config.outputs, &output) {
+                self.apply_output_config(&output);
+            }
+        }
+    }
+
+    /// Moves and scales `output` as its entry in the config says, if it has one.
+    pub fn apply_output_config(&mut self, output: &Output) {
+        let Some(config) = find_output_config(&self.config.outputs, output).cloned() else {
+            return;
+        };
+
+        let scale = config.scale as f64;
+        if output.current_scale().fractional_scale() != scale {
+            output.change_current_state(None, None, Some(Scale::Fractional(scale)), None);
+        }
+
+        let mut viewport = self.viewport(output);
+        viewport.origin = Point::from(config.pos).to_f64();
+        self.set_viewport(output, viewport);
+    }
+}
+
+fn find_output_config<'a>(
+    outputs: &'a [OutputConfig],
+    output: &Output,
+) -> Option<&'a OutputConfig> {
+    let name = output.name();
+    outputs.iter().find(|c| c.name == name)
+}
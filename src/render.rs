@@ -0,0 +1,68 @@
+use smithay::{
+    backend::renderer::{
+        element::{
+            memory::MemoryRenderBufferRenderElement, solid::SolidColorRenderElement,
+            surface::WaylandSurfaceRenderElement, AsRenderElements,
+        },
+        ImportAll, ImportMem, Renderer,
+    },
+    output::Output,
+};
+
+use crate::Clux;
+
+smithay::render_elements! {
+    pub CluxRenderElement<R> where R: ImportAll + ImportMem;
+    Surface=WaylandSurfaceRenderElement<R>,
+    Solid=SolidColorRenderElement,
+    Memory=MemoryRenderBufferRenderElement<R>,
+}
+
+impl Clux {
+    /// Collects the elements to draw on `output`, front to back, for either backend.
+    pub fn render_elements<R>(&self, renderer: &mut R, output: &Output) -> Vec<CluxRenderElement<R>>
+    where
+        R: Renderer + ImportAll + ImportMem,
+        R::TextureId: Send + Clone + 'static,
+    {
+        let mut elements = self.cursor_render_elements(renderer, output);
+
+        if self.overview.is_some() {
+            elements.extend(self.overview_render_elements(renderer, output));
+            return elements;
+        }
+
+        let Some(visible) = self.visible_canvas(output) else {
+            return elements;
+        };
+        let viewport = self.viewport(output);
+        let output_scale = output.current_scale().fractional_scale();
+
+        elements.extend(
+            self.space
+                .elements()
+                .rev()
+                .filter(|window| {
+                    self.decorated_bbox(window)
+                        .is_some_and(|bbox| bbox.to_f64().overlaps(visible))
+                })
+                .flat_map(|window| {
+                    let location =
+                        self.space.element_location(window).unwrap() - window.geometry().loc;
+                    let location = viewport.to_local(location.to_f64());
+
+                    let mut window_elements = window.render_elements::<CluxRenderElement<R>>(
+                        renderer,
+                        location.to_physical_precise_round(output_scale),
+                        (output_scale * viewport.zoom).into(),
+                        1.0,
+                    );
+                    window_elements
+                        .extend(self.decoration_render_elements(renderer, output, window));
+                    window_elements
+                }),
+        );
+
+        elements
+    }
+}
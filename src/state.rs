@@ -1,24 +1,36 @@
-use std::{ffi::OsString, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::OsString,
+    sync::Arc,
+};
 
 use smithay::{
     desktop::{PopupManager, Space, Window, WindowSurfaceType},
-    input::{keyboard::XkbConfig, Seat, SeatState},
+    input::{
+        keyboard::{Keycode, XkbConfig},
+        pointer::CursorImageStatus,
+        Seat, SeatState,
+    },
+    output::Output,
     reexports::{
-        calloop::{generic::Generic, EventLoop, Interest, LoopSignal, Mode, PostAction},
+        calloop::{generic::Generic, EventLoop, Interest, LoopHandle, LoopSignal, Mode, PostAction},
         wayland_server::{
             backend::{ClientData, ClientId, DisconnectReason},
             protocol::wl_surface::WlSurface,
             Display, DisplayHandle,
         },
     },
-    utils::{Logical, Point},
+    utils::{Logical, Point, Serial},
     wayland::{
         compositor::{CompositorClientState, CompositorState},
         output::OutputManagerState,
+        pointer_gestures::PointerGesturesState,
+        relative_pointer::RelativePointerManagerState,
         selection::data_device::DataDeviceState,
-        shell::xdg::XdgShellState,
+        shell::xdg::{decoration::XdgDecorationState, XdgShellState},
         shm::ShmState,
         socket::ListeningSocketSource,
+        tablet_manager::TabletManagerState,
     },
 };
 
@@ -28,74 +40,165 @@ pub struct Clux {
     pub display_handle: DisplayHandle,
 
     pub space: Space<Window>,
+    pub loop_handle: LoopHandle<'static, Clux>,
     pub loop_signal: LoopSignal,
+    pub ipc: Option<crate::ipc::IpcServer>,
 
     pub compositor_state: CompositorState,
     pub xdg_shell_state: XdgShellState,
+    pub xdg_decoration_state: XdgDecorationState,
     pub shm_state: ShmState,
     pub output_manager_state: OutputManagerState,
     pub seat_state: SeatState<Clux>,
     pub data_device_state: DataDeviceState,
+    pub relative_pointer_manager_state: RelativePointerManagerState,
+    pub pointer_gestures_state: PointerGesturesState,
+    pub tablet_manager_state: TabletManagerState,
     pub popups: PopupManager,
     pub config: crate::config::CluxConfig,
+    pub bindings: crate::bindings::BindingTable,
+    /// Binding mode whose bindings are active.
+    pub mode: String,
+    /// Keys whose press triggered a binding, so their release is kept from clients too.
+    pub suppressed_keys: HashSet<Keycode>,
+    /// The `[[keyboard.devices]]` entry whose keymap is installed, `None` for `[keyboard]`'s own.
+    pub keymap_device: Option<usize>,
+    /// Compiled keymaps of the keyboard config.
+    pub keymaps: crate::keyboard::Keymaps,
+    /// Titlebar button the left button went down on, clicked if it comes up over it too.
+    pub pressed_decoration_button: Option<(Window, crate::decorations::DecorationPart)>,
+    /// Window whose layout the keyboard has, with per-window layouts.
+    pub layout_window: Option<crate::window::WindowId>,
+    pub overview: Option<crate::overview::Overview>,
+    pub viewports: HashMap<Output, crate::canvas::Viewport>,
+    /// The touchpad gesture in progress.
+    pub gesture: Option<crate::gestures::ActiveGesture>,
+    /// Horizontal and vertical scrolling towards the next scroll binding step, in v120 units.
+    pub scroll_accumulator: [f64; 2],
+    /// Cursor asked for by the client under the pointer.
+    pub cursor_status: CursorImageStatus,
+    pub cursors: crate::cursor::Cursors,
+    /// Canvas position of the tablet tool in proximity, if any.
+    pub tablet_tool_location: Option<Point<f64, Logical>>,
+    /// Cursor asked for by the client under the tablet tool.
+    pub tablet_cursor_status: CursorImageStatus,
+    /// libinput devices, kept to apply `[input]` again when the config changes.
+    pub input_devices: Vec<smithay::reexports::input::Device>,
 
     pub seat: Seat<Self>,
 }
 
 impl Clux {
-    pub fn new(event_loop: &mut EventLoop<Self>, display: Display<Self>) -> Self {
+    pub fn new(event_loop: &mut EventLoop<'static, Self>, display: Display<Self>) -> Self {
+        let state = Self::with_config(event_loop, display, crate::config::load_config());
+
+        let config_path = crate::config::config_path();
+        if let Err(e) = crate::reload::watch_config(&state.loop_handle, &config_path) {
+            tracing::warn!("Not watching {} for changes: {}", config_path.display(), e);
+        }
+
+        state
+    }
+
+    pub fn with_config(
+        event_loop: &mut EventLoop<'static, Self>,
+        display: Display<Self>,
+        clux_config: crate::config::CluxConfig,
+    ) -> Self {
         let start_time = std::time::Instant::now();
         let dh = display.handle();
 
-        let clux_config = crate::config::load_config();
-
         let compositor_state = CompositorState::new::<Self>(&dh);
         let xdg_shell_state = XdgShellState::new::<Self>(&dh);
+        let xdg_decoration_state = XdgDecorationState::new::<Self>(&dh);
         let shm_state = ShmState::new::<Self>(&dh, vec![]);
         let popups = PopupManager::default();
         let output_manager_state = OutputManagerState::new_with_xdg_output::<Self>(&dh);
         let data_device_state = DataDeviceState::new::<Self>(&dh);
+        let relative_pointer_manager_state = RelativePointerManagerState::new::<Self>(&dh);
+        let pointer_gestures_state = PointerGesturesState::new::<Self>(&dh);
+        let tablet_manager_state = TabletManagerState::new::<Self>(&dh);
 
         let mut seat_state = SeatState::new();
         let mut seat: Seat<Self> = seat_state.new_wl_seat(&dh, "clux-seat");
 
+        let keyboard = &clux_config.keyboard;
+        let keymaps = crate::keyboard::Keymaps::compile_lenient(keyboard);
+        // Smithay would fail on a layout xkb rejected just now, xkb's default one stands in.
+        let xkb_config = match keymaps.get(None) {
+            Some(_) => keyboard.xkb_config(),
+            None => XkbConfig::default(),
+        };
         seat.add_keyboard(
-            XkbConfig {
-                layout: &clux_config.keyboard.layout,
-                variant: &clux_config.keyboard.variant,
-                options: clux_config.keyboard.options.clone(),
-                ..Default::default()
-            },
-            200,
-            25,
+            xkb_config,
+            keyboard.repeat_delay as i32,
+            keyboard.repeat_rate as i32,
         )
         .unwrap();
 
         seat.add_pointer();
+        seat.add_touch();
 
         let space = Space::default();
         let socket_name = Self::init_wayland_listener(display, event_loop);
+        let loop_handle = event_loop.handle();
         let loop_signal = event_loop.get_signal();
 
-        Self {
+        let ipc = crate::ipc::IpcServer::start(&loop_handle, &socket_name)
+            .map_err(|e| tracing::error!("Failed to start the IPC socket: {}", e))
+            .ok();
+
+        let mut state = Self {
             start_time,
             display_handle: dh,
             space,
+            loop_handle,
             loop_signal,
+            ipc,
             socket_name,
             compositor_state,
             xdg_shell_state,
+            xdg_decoration_state,
             shm_state,
             output_manager_state,
             seat_state,
             data_device_state,
+            relative_pointer_manager_state,
+            pointer_gestures_state,
+            tablet_manager_state,
             popups,
             seat,
+            bindings: crate::bindings::BindingTable::new(&clux_config),
+            mode: crate::actions::DEFAULT_MODE.to_string(),
+            suppressed_keys: HashSet::new(),
+            keymap_device: None,
+            keymaps,
+            pressed_decoration_button: None,
+            layout_window: None,
+            cursor_status: CursorImageStatus::default_named(),
+            cursors: crate::cursor::Cursors::new(&clux_config.cursor),
+            tablet_tool_location: None,
+            tablet_cursor_status: CursorImageStatus::default_named(),
+            input_devices: Vec::new(),
             config: clux_config,
+            overview: None,
+            viewports: HashMap::new(),
+            gesture: None,
+            scroll_accumulator: [0.0; 2],
+        };
+
+        // The keyboard starts out with the layout, which a keymap file then replaces.
+        if state.config.keyboard.keymap_file.is_some() {
+            state.apply_keymap();
         }
+
+        state
     }
 
-    fn init_wayland_listener(display: Display<Clux>, event_loop: &mut EventLoop<Self>) -> OsString {
+    fn init_wayland_listener(
+        display: Display<Clux>,
+        event_loop: &mut EventLoop<'static, Self>,
+    ) -> OsString {
         let listening_socket = ListeningSocketSource::new_auto().unwrap();
         let socket_name = listening_socket.socket_name().to_os_string();
         let loop_handle = event_loop.handle();
@@ -128,6 +231,11 @@ impl Clux {
         &self,
         pos: Point<f64, Logical>,
     ) -> Option<(WlSurface, Point<f64, Logical>)> {
+        // Decorations belong to the compositor, whatever is below them.
+        if self.decoration_under(pos).is_some() {
+            return None;
+        }
+
         self.space
             .element_under(pos)
             .and_then(|(window, location)| {
@@ -137,16 +245,51 @@ impl Clux {
             })
     }
 
-    pub fn map_output(&mut self, output: &smithay::output::Output, location: Point<i32, Logical>) {
-        let pos = self
-            .config
-            .outputs
-            .iter()
-            .find(|c| c.name == output.name())
-            .map(|c| Point::from(c.pos))
-            .unwrap_or(location);
+    pub fn window_for_surface(&self, surface: &WlSurface) -> Option<Window> {
+        self.space
+            .elements()
+            .find(|w| w.toplevel().unwrap().wl_surface() == surface)
+            .cloned()
+    }
+
+    pub fn focused_window(&self) -> Option<Window> {
+        let focus = self.seat.get_keyboard()?.current_focus()?;
+        self.window_for_surface(&focus)
+    }
+
+    /// Raises `window` to the top of the stack and gives it keyboard focus.
+    pub fn focus_window(&mut self, window: &Window, serial: Serial) {
+        self.space.raise_element(window, true);
+        if let Some(toplevel) = window.toplevel() {
+            let keyboard = self.seat.get_keyboard().unwrap();
+            keyboard.set_focus(self, Some(toplevel.wl_surface().clone()), serial);
+        }
+    }
+
+    pub fn spawn(&self, command: &str) -> std::io::Result<()> {
+        let mut child = std::process::Command::new("sh");
+        child
+            .arg("-c")
+            .arg(command)
+            .env("XCURSOR_THEME", &self.config.cursor.theme)
+            .env("XCURSOR_SIZE", self.config.cursor.size.to_string());
+        if let Some(ipc) = &self.ipc {
+            child.env(crate::ipc::protocol::SOCKET_ENV, &ipc.path);
+        }
+
+        child.spawn().map(|_| ())
+    }
+
+    pub fn map_output(&mut self, output: &Output, location: Point<i32, Logical>) {
+        self.set_viewport(
+            output,
+            crate::canvas::Viewport {
+                origin: location.to_f64(),
+                zoom: 1.0,
+            },
+        );
 
-        self.space.map_output(output, pos);
+        self.apply_output_config(output);
     }
 }
 
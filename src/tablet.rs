@@ -0,0 +1,171 @@
+//! Graphics tablets, forwarded to clients through the tablet protocol.
+//!
+//! Tablet tools move on their own, so the pointer stays where it is while a pen is used. The tool
+//! gets its own cursor, the one the client under it asks for or else the default one.
+
+use smithay::{
+    backend::input::{
+        AbsolutePositionEvent, Device, DeviceCapability, Event, InputBackend, ProximityState,
+        TabletToolButtonEvent, TabletToolEvent, TabletToolProximityEvent, TabletToolTipEvent,
+        TabletToolTipState,
+    },
+    input::pointer::CursorImageStatus,
+    utils::{Logical, Point, Rectangle, SERIAL_COUNTER},
+    wayland::tablet_manager::{TabletDescriptor, TabletSeatTrait},
+};
+
+use crate::{config::DeviceKind, state::Clux};
+
+impl Clux {
+    pub fn add_tablet(&mut self, device: &impl Device) {
+        if device.has_capability(DeviceCapability::TabletTool) {
+            let tablet_seat = self.seat.tablet_seat();
+            tablet_seat.add_tablet::<Self>(&self.display_handle, &TabletDescriptor::from(device));
+        }
+    }
+
+    pub fn remove_tablet(&mut self, device: &impl Device) {
+        if device.has_capability(DeviceCapability::TabletTool) {
+            let tablet_seat = self.seat.tablet_seat();
+            tablet_seat.remove_tablet(&TabletDescriptor::from(device));
+
+            // Tools belong to no tablet in particular, they go once the last tablet is gone.
+            if tablet_seat.count_tablets() == 0 {
+                tablet_seat.clear_tools();
+                self.tablet_tool_location = None;
+            }
+        }
+    }
+
+    /// Canvas position of a tablet tool, on the output and region the tablet is mapped to.
+    fn tablet_location<I: InputBackend>(
+        &self,
+        event: &impl AbsolutePositionEvent<I>,
+    ) -> Option<Point<f64, Logical>> {
+        let device = event.device();
+        let output = self.mapped_output(&device, DeviceKind::Tablet)?;
+        let size = self.space.output_geometry(&output)?.size;
+
+        let region = match self
+            .config
+            .input
+            .mapped_region(&device.name(), &[DeviceKind::Tablet])
+        {
+            Some([x, y, width, height]) => Rectangle::new((x, y).into(), (width, height).into()),
+            None => Rectangle::from_size(size),
+        };
+        let local = region.loc.to_f64() + event.position_transformed(region.size);
+
+        Some(self.viewport(&output).to_canvas(local))
+    }
+
+    pub fn tablet_proximity<I: InputBackend>(&mut self, event: I::TabletToolProximityEvent) {
+        let tablet_seat = self.seat.tablet_seat();
+        let descriptor = event.tool();
+
+        match event.state() {
+            ProximityState::In => {
+                let Some(pos) = self.tablet_location(&event) else {
+                    return;
+                };
+                let dh = self.display_handle.clone();
+                let tool = tablet_seat.add_tool::<Self>(self, &dh, &descriptor);
+                let tablet = tablet_seat.get_tablet(&TabletDescriptor::from(&event.device()));
+
+                self.tablet_tool_location = Some(pos);
+                self.tablet_cursor_status = CursorImageStatus::default_named();
+
+                if let (Some(under), Some(tablet)) = (self.surface_under(pos), tablet) {
+                    tool.proximity_in(
+                        pos,
+                        under,
+                        &tablet,
+                        SERIAL_COUNTER.next_serial(),
+                        event.time_msec(),
+                    );
+                }
+            }
+            ProximityState::Out => {
+                if let Some(tool) = tablet_seat.get_tool(&descriptor) {
+                    tool.proximity_out(event.time_msec());
+                }
+                self.tablet_tool_location = None;
+            }
+        }
+    }
+
+    pub fn tablet_axis<I: InputBackend>(&mut self, event: I::TabletToolAxisEvent) {
+        let Some(pos) = self.tablet_location(&event) else {
+            return;
+        };
+        self.tablet_tool_location = Some(pos);
+
+        let tablet_seat = self.seat.tablet_seat();
+        let tablet = tablet_seat.get_tablet(&TabletDescriptor::from(&event.device()));
+        let Some((tool, tablet)) = tablet_seat.get_tool(&event.tool()).zip(tablet) else {
+            return;
+        };
+
+        if event.pressure_has_changed() {
+            tool.pressure(event.pressure());
+        }
+        if event.distance_has_changed() {
+            tool.distance(event.distance());
+        }
+        if event.tilt_has_changed() {
+            tool.tilt(event.tilt());
+        }
+        if event.slider_has_changed() {
+            tool.slider_position(event.slider_position());
+        }
+        if event.rotation_has_changed() {
+            tool.rotation(event.rotation());
+        }
+        if event.wheel_has_changed() {
+            tool.wheel(event.wheel_delta(), event.wheel_delta_discrete());
+        }
+
+        let under = self.surface_under(pos);
+        tool.motion(
+            pos,
+            under,
+            &tablet,
+            SERIAL_COUNTER.next_serial(),
+            event.time_msec(),
+        );
+    }
+
+    pub fn tablet_tip<I: InputBackend>(&mut self, event: I::TabletToolTipEvent) {
+        let Some(tool) = self.seat.tablet_seat().get_tool(&event.tool()) else {
+            return;
+        };
+
+        match event.tip_state() {
+            TabletToolTipState::Down => {
+                let serial = SERIAL_COUNTER.next_serial();
+                tool.tip_down(serial, event.time_msec());
+
+                // Like a click, touching the tablet focuses the window under the tool.
+                let window = self
+                    .tablet_tool_location
+                    .and_then(|pos| self.space.element_under(pos))
+                    .map(|(window, _)| window.clone());
+                if let Some(window) = window {
+                    self.focus_window(&window, serial);
+                }
+            }
+            TabletToolTipState::Up => tool.tip_up(event.time_msec()),
+        }
+    }
+
+    pub fn tablet_button<I: InputBackend>(&mut self, event: I::TabletToolButtonEvent) {
+        if let Some(tool) = self.seat.tablet_seat().get_tool(&event.tool()) {
+            tool.button(
+                event.button(),
+                event.button_state(),
+                SERIAL_COUNTER.next_serial(),
+                event.time_msec(),
+            );
+        }
+    }
+}
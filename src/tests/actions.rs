@@ -0,0 +1,82 @@
+use smithay::utils::SERIAL_COUNTER;
+use wayland_protocols::xdg::shell::client::xdg_toplevel;
+
+use super::{
+    client::DEFAULT_WINDOW_SIZE,
+    fixture::{Fixture, KEY_LEFTMETA, KEY_Q, OUTPUT_SIZE},
+};
+use crate::actions::Action;
+
+const CLOSE_CONFIG: &str = r#"
+[keybindings.close]
+combo = "logo+q"
+action = "close"
+"#;
+
+#[test]
+fn close_binding_closes_focused_window() {
+    let mut f = Fixture::with_toml(CLOSE_CONFIG);
+    let mut client = f.client();
+    let idx = client.create_window();
+    f.roundtrip(&mut client);
+
+    let window = f.window(&client, idx);
+    f.state.focus_window(&window, SERIAL_COUNTER.next_serial());
+    f.press_combo(&[KEY_LEFTMETA, KEY_Q]);
+    f.roundtrip(&mut client);
+
+    assert!(client.state.windows[idx].close_requested);
+}
+
+#[test]
+fn toggle_maximize_fills_output_and_restores() {
+    let (mut f, mut client, idx) = Fixture::with_window((300, 200));
+    let window = f.window(&client, idx);
+    f.state.focus_window(&window, SERIAL_COUNTER.next_serial());
+
+    f.state.run_action(Action::ToggleMaximize, "");
+    f.roundtrip(&mut client);
+
+    let configure = client.last_configure(idx);
+    assert_eq!(configure.size, OUTPUT_SIZE);
+    assert!(configure.states.contains(&xdg_toplevel::State::Maximized));
+    assert_eq!(f.window_location(&client, idx), (0, 0).into());
+
+    f.state.run_action(Action::ToggleMaximize, "");
+    f.roundtrip(&mut client);
+
+    let configure = client.last_configure(idx);
+    assert_eq!(configure.size, DEFAULT_WINDOW_SIZE);
+    assert!(!configure.states.contains(&xdg_toplevel::State::Maximized));
+    assert_eq!(f.window_location(&client, idx), (300, 200).into());
+}
+
+#[test]
+fn focus_next_and_prev_cycle_through_the_stack() {
+    let mut f = Fixture::new();
+    let mut client = f.client();
+    let windows: Vec<usize> = (0..3).map(|_| client.create_window()).collect();
+    f.roundtrip(&mut client);
+
+    let top = f.window(&client, windows[2]);
+    f.state.focus_window(&top, SERIAL_COUNTER.next_serial());
+
+    f.state.run_action(Action::FocusNext, "");
+    f.roundtrip(&mut client);
+    assert_eq!(
+        f.state.focused_window(),
+        Some(f.window(&client, windows[0]))
+    );
+    assert_eq!(
+        f.state.space.elements().last(),
+        Some(&f.window(&client, windows[0]))
+    );
+
+    f.state.run_action(Action::FocusPrev, "");
+    f.roundtrip(&mut client);
+    assert_eq!(f.state.focused_window(), Some(top.clone()));
+    assert_eq!(
+        f.state.space.elements().next(),
+        Some(&f.window(&client, windows[0]))
+    );
+}
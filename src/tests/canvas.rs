@@ -0,0 +1,47 @@
+use smithay::utils::Point;
+
+use super::{client::TestClient, fixture::Fixture};
+
+#[test]
+fn windows_are_on_the_outputs_showing_them_at_any_zoom() {
+    let (mut f, mut client, idx) = Fixture::with_window((1200, 100));
+    let window = f.window(&client, idx);
+    let surface = client.state.windows[idx].surface.clone();
+    let on_output = |client: &TestClient| client.state.entered.contains(&surface);
+    let output = f.output.clone();
+
+    f.roundtrip(&mut client);
+    assert!(on_output(&client));
+
+    // At twice the size the output only shows the canvas up to x = 960.
+    f.state.zoom_output(&output, 2.0, Point::from((0.0, 0.0)));
+    f.roundtrip(&mut client);
+    assert!(!on_output(&client));
+
+    // At half the size it shows it up to x = 3840.
+    f.state.zoom_output(&output, 0.5, Point::from((0.0, 0.0)));
+    f.state.space.map_element(window, (2500, 100), false);
+    f.roundtrip(&mut client);
+    assert!(on_output(&client));
+
+    f.state.zoom_output(&output, 1.0, Point::from((0.0, 0.0)));
+    f.roundtrip(&mut client);
+    assert!(!on_output(&client));
+}
+
+#[test]
+fn new_windows_open_where_the_output_is_panned_to() {
+    let mut f = Fixture::new();
+    let output = f.output.clone();
+    f.state.zoom_output(&output, 2.0, Point::from((0.0, 0.0)));
+    f.state.pan_output(&output, Point::from((3000.0, -2000.0)));
+    let visible = f.state.visible_canvas(&output).unwrap();
+
+    let mut client = f.client();
+    let idx = client.create_window();
+    f.roundtrip(&mut client);
+
+    let window = f.window(&client, idx);
+    let geometry = f.state.space.element_geometry(&window).unwrap();
+    assert!(visible.contains_rect(geometry.to_f64()));
+}
@@ -0,0 +1,173 @@
+use crate::check::check_source;
+
+fn locations(source: &str) -> Vec<(Option<(usize, usize)>, String)> {
+    check_source(source)
+        .into_iter()
+        .map(|d| (d.location(source), d.message))
+        .collect()
+}
+
+#[test]
+fn example_config_is_valid() {
+    assert_eq!(locations(include_str!("../../config.toml")), Vec::new());
+}
+
+#[test]
+fn reports_bad_combos_at_their_position() {
+    let source = r#"[keybindings.terminal]
+combo = "ctrl+shft+x"
+command = "foot"
+
+[keybindings.launcher]
+combo = "logo+notakey"
+command = "fuzzel"
+"#;
+
+    let diagnostics = locations(source);
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].0, Some((2, 9)));
+    assert!(diagnostics[0].1.contains("`shft`"));
+    assert_eq!(diagnostics[1].0, Some((6, 9)));
+    assert!(diagnostics[1].1.contains("`notakey`"));
+}
+
+#[test]
+fn reports_conflicting_bindings() {
+    let source = r#"[keybindings.a]
+combo = "ctrl+shift+Return"
+command = "foot"
+
+[keybindings.b]
+combo = "shift+ctrl+return"
+command = "alacritty"
+"#;
+
+    let diagnostics = locations(source);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].0, Some((6, 9)));
+    assert!(diagnostics[0].1.contains("already bound by `a`"));
+}
+
+#[test]
+fn reports_unknown_keys_layouts_and_outputs() {
+    let diagnostics = locations("[keyboard]\nlayuot = \"de\"\n");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].0, Some((2, 1)));
+
+    let source = r#"[keyboard]
+layout = "nosuchlayout"
+
+[[outputs]]
+name = "HDMI-1"
+pos = [0, 0]
+scale = 0.0
+"#;
+
+    let diagnostics = locations(source);
+    assert_eq!(diagnostics.len(), 3);
+    assert_eq!(diagnostics[0].0, Some((2, 10)));
+    assert_eq!(diagnostics[1].0, Some((5, 8)));
+    assert_eq!(diagnostics[2].0, Some((7, 9)));
+}
+
+#[test]
+fn reports_unknown_modes() {
+    let source = r#"[keybindings.resize]
+combo = "logo+r"
+action = { enter_mode = "resize" }
+
+[modes.rezise.grow]
+combo = "right"
+action = "grow_width"
+"#;
+
+    let diagnostics = locations(source);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].0, Some((3, 10)));
+    assert!(diagnostics[0].1.contains("no [modes.resize]"));
+}
+
+#[test]
+fn reports_bad_input_settings() {
+    let source = r#"[input]
+tap = true
+accel_speed = 1.5
+
+[[input.devices]]
+type = "touchpad"
+accel_speed = -2.0
+
+[[input.devices]]
+natural_scroll = true
+"#;
+
+    let diagnostics = locations(source);
+    assert_eq!(diagnostics.len(), 3);
+    assert_eq!(diagnostics[0].0, Some((3, 15)));
+    assert!(diagnostics[0].1.contains("accel_speed 1.5"));
+    assert_eq!(diagnostics[1].0, Some((7, 15)));
+    assert!(diagnostics[1].1.contains("accel_speed -2"));
+    assert!(diagnostics[2].1.contains("neither a `name` nor a `type`"));
+}
+
+#[test]
+fn reports_empty_device_region() {
+    let source = r#"[[input.devices]]
+type = "tablet"
+region = [0, 0, 1920, 0]
+"#;
+
+    let diagnostics = locations(source);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].0, Some((3, 10)));
+    assert!(diagnostics[0].1.contains("1920x0"));
+}
+
+#[test]
+fn reports_unreadable_keymap_file() {
+    let source = r#"[keyboard]
+keymap_file = "/nonexistent/clux.xkb"
+"#;
+
+    let diagnostics = locations(source);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].0, Some((2, 15)));
+    assert!(diagnostics[0]
+        .1
+        .contains("failed to read keymap file /nonexistent/clux.xkb"));
+}
+
+#[test]
+fn reports_broken_device_keymaps() {
+    let source = r#"[keyboard]
+layout = "us"
+
+[[keyboard.devices]]
+name = "USB Keyboard"
+layout = "nosuchlayout"
+
+[[keyboard.devices]]
+name = "Other Keyboard"
+keymap_file = "/nonexistent/clux.xkb"
+"#;
+
+    let diagnostics = locations(source);
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].0, Some((6, 10)));
+    assert!(diagnostics[0].1.contains("`nosuchlayout`"));
+    assert_eq!(diagnostics[1].0, Some((10, 15)));
+}
+
+#[test]
+fn reports_invalid_colors() {
+    let source = r##"[decorations]
+focused_color = "#12345"
+"##;
+
+    let diagnostics = locations(source);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].0, Some((3, 17)));
+    assert!(diagnostics[0]
+        .1
+        .contains("invalid color `#12345`, expected #rrggbb or #rrggbbaa"));
+}
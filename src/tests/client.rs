@@ -0,0 +1,819 @@
+use std::{
+    fs::File,
+    os::{fd::AsFd, unix::net::UnixStream},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use wayland_client::{
+    delegate_noop, event_created_child,
+    protocol::{
+        wl_buffer::WlBuffer,
+        wl_compositor::WlCompositor,
+        wl_keyboard::{self, WlKeyboard},
+        wl_output::WlOutput,
+        wl_pointer::{self, WlPointer},
+        wl_registry::{self, WlRegistry},
+        wl_seat::{self, WlSeat},
+        wl_shm::{self, WlShm},
+        wl_shm_pool::WlShmPool,
+        wl_surface::{self, WlSurface},
+        wl_touch::{self, WlTouch},
+    },
+    Connection, Dispatch, EventQueue, QueueHandle, WEnum,
+};
+use wayland_protocols::{
+    wp::pointer_gestures::zv1::client::{
+        zwp_pointer_gesture_hold_v1::{self, ZwpPointerGestureHoldV1},
+        zwp_pointer_gesture_pinch_v1::{self, ZwpPointerGesturePinchV1},
+        zwp_pointer_gesture_swipe_v1::{self, ZwpPointerGestureSwipeV1},
+        zwp_pointer_gestures_v1::ZwpPointerGesturesV1,
+    },
+    wp::relative_pointer::zv1::client::{
+        zwp_relative_pointer_manager_v1::ZwpRelativePointerManagerV1,
+        zwp_relative_pointer_v1::{self, ZwpRelativePointerV1},
+    },
+    wp::tablet::zv2::client::{
+        zwp_tablet_manager_v2::ZwpTabletManagerV2,
+        zwp_tablet_seat_v2::{self, ZwpTabletSeatV2},
+        zwp_tablet_tool_v2::{self, ZwpTabletToolV2},
+        zwp_tablet_v2::ZwpTabletV2,
+    },
+    xdg::decoration::zv1::client::{
+        zxdg_decoration_manager_v1::ZxdgDecorationManagerV1,
+        zxdg_toplevel_decoration_v1::{self, ZxdgToplevelDecorationV1},
+    },
+    xdg::shell::client::{
+        xdg_popup::XdgPopup,
+        xdg_positioner::XdgPositioner,
+        xdg_surface::{self, XdgSurface},
+        xdg_toplevel::{self, XdgToplevel},
+        xdg_wm_base::{self, XdgWmBase},
+    },
+};
+
+/// Size a window commits when the compositor lets it pick one.
+pub const DEFAULT_WINDOW_SIZE: (i32, i32) = (200, 100);
+
+/// A Wayland client living in the test process.
+pub struct TestClient {
+    connection: Connection,
+    queue: EventQueue<ClientState>,
+    pub state: ClientState,
+}
+
+#[derive(Default)]
+pub struct ClientState {
+    compositor: Option<WlCompositor>,
+    shm: Option<WlShm>,
+    wm_base: Option<XdgWmBase>,
+    decoration_manager: Option<ZxdgDecorationManagerV1>,
+    outputs: Vec<WlOutput>,
+    pub seat: Option<WlSeat>,
+    keyboard: Option<WlKeyboard>,
+    pointer: Option<WlPointer>,
+    touch: Option<WlTouch>,
+    pointer_gestures: Option<ZwpPointerGesturesV1>,
+    relative_pointer_manager: Option<ZwpRelativePointerManagerV1>,
+    tablet_manager: Option<ZwpTabletManagerV2>,
+    tablet_seat: Option<ZwpTabletSeatV2>,
+
+    pub windows: Vec<TestWindow>,
+    pub popups: Vec<TestPopup>,
+
+    /// Surfaces the compositor said are on an output, once for every output they are on.
+    pub entered: Vec<WlSurface>,
+    pub keyboard_focus: Option<WlSurface>,
+    /// Keys pressed while the client had keyboard focus, as evdev codes.
+    pub pressed_keys: Vec<u32>,
+    /// Key repeat rate and delay announced by the compositor.
+    pub repeat_info: Option<(i32, i32)>,
+    /// Number of keymaps sent by the compositor, the first one included.
+    pub keymaps: usize,
+    /// Serial of the last time the pointer entered one of the client's surfaces.
+    pub pointer_enter_serial: Option<u32>,
+    /// Serial of the last button press, needed for move and resize requests.
+    pub last_button_serial: Option<u32>,
+    /// Surface and surface-local position of every touch down received.
+    pub touch_downs: Vec<(WlSurface, (f64, f64))>,
+    /// Serial of the last touch down, needed for move requests made by touch.
+    pub last_touch_serial: Option<u32>,
+    /// Sum of the vertical scroll values received.
+    pub scrolled: f64,
+    /// Number of vertical scroll stops received.
+    pub scroll_stops: usize,
+    /// Unaccelerated motion of every relative pointer event received, in order.
+    pub relative_motions: Vec<(f64, f64)>,
+    /// Every pointer gesture event received, in order.
+    pub gestures: Vec<GestureEvent>,
+    /// Number of tablets and tablet tools announced.
+    pub tablets: usize,
+    pub tablet_tools: usize,
+    /// Every tablet tool event received, in order, leaving out frames.
+    pub tablet_events: Vec<TabletEvent>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TabletEvent {
+    ProximityIn(WlSurface),
+    ProximityOut,
+    Down,
+    Up,
+    Motion { x: f64, y: f64 },
+    Button { button: u32, pressed: bool },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GestureEvent {
+    SwipeBegin { fingers: u32 },
+    SwipeUpdate { dx: f64, dy: f64 },
+    SwipeEnd { cancelled: bool },
+    PinchBegin { fingers: u32 },
+    PinchUpdate { scale: f64 },
+    PinchEnd { cancelled: bool },
+    HoldBegin { fingers: u32 },
+    HoldEnd { cancelled: bool },
+}
+
+pub struct TestWindow {
+    pub surface: WlSurface,
+    pub xdg_surface: XdgSurface,
+    pub toplevel: XdgToplevel,
+    /// Every configure sequence received, in order.
+    pub configures: Vec<Configure>,
+    pending: Configure,
+    /// Whether the compositor asked the window to close.
+    pub close_requested: bool,
+    /// Decorations the compositor settled on, for windows that negotiate them.
+    pub decoration_mode: Option<zxdg_toplevel_decoration_v1::Mode>,
+}
+
+pub struct TestPopup {
+    pub surface: WlSurface,
+    pub configured: bool,
+    size: (i32, i32),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Configure {
+    pub serial: u32,
+    pub size: (i32, i32),
+    pub states: Vec<xdg_toplevel::State>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Role {
+    Toplevel(usize),
+    Popup(usize),
+}
+
+impl TestClient {
+    pub fn connect(stream: UnixStream) -> Self {
+        stream.set_nonblocking(true).unwrap();
+        let connection = Connection::from_socket(stream).unwrap();
+        let queue = connection.new_event_queue();
+
+        connection.display().get_registry(&queue.handle(), ());
+
+        Self {
+            connection,
+            queue,
+            state: ClientState::default(),
+        }
+    }
+
+    pub fn flush(&mut self) {
+        let _ = self.connection.flush();
+    }
+
+    /// Reads whatever the compositor sent and handles it, without blocking.
+    pub fn dispatch(&mut self) {
+        if let Some(guard) = self.connection.prepare_read() {
+            let _ = guard.read();
+        }
+        self.queue.dispatch_pending(&mut self.state).unwrap();
+    }
+
+    /// Creates a toplevel and returns its index in `state.windows`.
+    pub fn create_window(&mut self) -> usize {
+        self.create_toplevel(None)
+    }
+
+    /// Creates a toplevel that negotiates its decorations, asking for `mode` or leaving the
+    /// choice to the compositor.
+    pub fn create_decorated_window(
+        &mut self,
+        mode: Option<zxdg_toplevel_decoration_v1::Mode>,
+    ) -> usize {
+        self.create_toplevel(Some(mode))
+    }
+
+    fn create_toplevel(
+        &mut self,
+        decoration: Option<Option<zxdg_toplevel_decoration_v1::Mode>>,
+    ) -> usize {
+        let qh = self.queue.handle();
+        let idx = self.state.windows.len();
+
+        let surface = self
+            .state
+            .compositor
+            .as_ref()
+            .unwrap()
+            .create_surface(&qh, ());
+        let xdg_surface = self.state.wm_base.as_ref().unwrap().get_xdg_surface(
+            &surface,
+            &qh,
+            Role::Toplevel(idx),
+        );
+        let toplevel = xdg_surface.get_toplevel(&qh, idx);
+
+        // Decorations are negotiated before the first buffer is attached.
+        if let Some(mode) = decoration {
+            let manager = self.state.decoration_manager.as_ref().unwrap();
+            let decoration = manager.get_toplevel_decoration(&toplevel, &qh, idx);
+            if let Some(mode) = mode {
+                decoration.set_mode(mode);
+            }
+        }
+
+        // The initial commit without a buffer asks for the first configure.
+        surface.commit();
+
+        self.state.windows.push(TestWindow {
+            surface,
+            xdg_surface,
+            toplevel,
+            configures: Vec::new(),
+            pending: Configure::default(),
+            close_requested: false,
+            decoration_mode: None,
+        });
+
+        idx
+    }
+
+    /// Opens a popup of `size` below the top-left corner of window `parent`.
+    pub fn create_popup(&mut self, parent: usize, size: (i32, i32)) -> usize {
+        let qh = self.queue.handle();
+        let idx = self.state.popups.len();
+        let wm_base = self.state.wm_base.as_ref().unwrap();
+
+        let positioner = wm_base.create_positioner(&qh, ());
+        positioner.set_size(size.0, size.1);
+        positioner.set_anchor_rect(0, 0, 1, 1);
+
+        let surface = self
+            .state
+            .compositor
+            .as_ref()
+            .unwrap()
+            .create_surface(&qh, ());
+        let xdg_surface = wm_base.get_xdg_surface(&surface, &qh, Role::Popup(idx));
+        xdg_surface.get_popup(
+            Some(&self.state.windows[parent].xdg_surface),
+            &positioner,
+            &qh,
+            (),
+        );
+        positioner.destroy();
+        surface.commit();
+
+        self.state.popups.push(TestPopup {
+            surface,
+            configured: false,
+            size,
+        });
+
+        idx
+    }
+
+    /// Sets the cursor shown over the client: a surface of `size` with its hotspot at `hotspot`,
+    /// or no cursor at all.
+    pub fn set_cursor(&mut self, cursor: Option<((i32, i32), (i32, i32))>) {
+        let qh = self.queue.handle();
+        let pointer = self.state.pointer.as_ref().unwrap();
+        let serial = self.state.pointer_enter_serial.unwrap();
+
+        match cursor {
+            Some((size, hotspot)) => {
+                let surface = self
+                    .state
+                    .compositor
+                    .as_ref()
+                    .unwrap()
+                    .create_surface(&qh, ());
+                pointer.set_cursor(serial, Some(&surface), hotspot.0, hotspot.1);
+                surface.attach(Some(&self.state.create_buffer(size, &qh)), 0, 0);
+                surface.commit();
+            }
+            None => pointer.set_cursor(serial, None, 0, 0),
+        }
+    }
+
+    pub fn start_move(&mut self, idx: usize) {
+        let seat = self.state.seat.as_ref().unwrap();
+        let serial = self.state.last_button_serial.unwrap();
+        self.state.windows[idx].toplevel._move(seat, serial);
+    }
+
+    /// Asks to move window `idx` with the finger that last touched it.
+    pub fn start_touch_move(&mut self, idx: usize) {
+        let seat = self.state.seat.as_ref().unwrap();
+        let serial = self.state.last_touch_serial.unwrap();
+        self.state.windows[idx].toplevel._move(seat, serial);
+    }
+
+    pub fn start_resize(&mut self, idx: usize, edge: xdg_toplevel::ResizeEdge) {
+        let seat = self.state.seat.as_ref().unwrap();
+        let serial = self.state.last_button_serial.unwrap();
+        self.state.windows[idx].toplevel.resize(seat, serial, edge);
+    }
+
+    pub fn last_configure(&self, idx: usize) -> &Configure {
+        self.state.windows[idx]
+            .configures
+            .last()
+            .expect("window was never configured")
+    }
+}
+
+impl ClientState {
+    /// Creates a zero-filled shm buffer of `size`.
+    fn create_buffer(&self, size: (i32, i32), qh: &QueueHandle<Self>) -> WlBuffer {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let (width, height) = size;
+        let stride = width * 4;
+        let len = stride * height;
+
+        let path = std::env::temp_dir().join(format!(
+            "clux-test-buffer-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        file.set_len(len as u64).unwrap();
+
+        let pool = self
+            .shm
+            .as_ref()
+            .unwrap()
+            .create_pool(file.as_fd(), len, qh, ());
+        let buffer = pool.create_buffer(0, width, height, stride, wl_shm::Format::Argb8888, qh, ());
+        pool.destroy();
+
+        buffer
+    }
+}
+
+impl Dispatch<WlRegistry, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        registry: &WlRegistry,
+        event: wl_registry::Event,
+        _: &(),
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        if let wl_registry::Event::Global {
+            name,
+            interface,
+            version,
+        } = event
+        {
+            match interface.as_str() {
+                "wl_compositor" => {
+                    state.compositor = Some(registry.bind(name, version.min(5), qh, ()));
+                }
+                "wl_shm" => state.shm = Some(registry.bind(name, 1, qh, ())),
+                "xdg_wm_base" => state.wm_base = Some(registry.bind(name, version.min(5), qh, ())),
+                "zxdg_decoration_manager_v1" => {
+                    state.decoration_manager = Some(registry.bind(name, 1, qh, ()));
+                }
+                "wl_output" => {
+                    state
+                        .outputs
+                        .push(registry.bind(name, version.min(4), qh, ()));
+                }
+                "wl_seat" => state.seat = Some(registry.bind(name, version.min(7), qh, ())),
+                "zwp_tablet_manager_v2" => {
+                    state.tablet_manager = Some(registry.bind(name, 1, qh, ()));
+                }
+                "zwp_pointer_gestures_v1" => {
+                    state.pointer_gestures = Some(registry.bind(name, version.min(3), qh, ()));
+                }
+                "zwp_relative_pointer_manager_v1" => {
+                    state.relative_pointer_manager = Some(registry.bind(name, 1, qh, ()));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Dispatch<WlSurface, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        surface: &WlSurface,
+        event: wl_surface::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_surface::Event::Enter { .. } => state.entered.push(surface.clone()),
+            wl_surface::Event::Leave { .. } => {
+                if let Some(idx) = state.entered.iter().position(|s| s == surface) {
+                    state.entered.remove(idx);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlSeat, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        seat: &WlSeat,
+        event: wl_seat::Event,
+        _: &(),
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        if let wl_seat::Event::Capabilities {
+            capabilities: WEnum::Value(capabilities),
+        } = event
+        {
+            if capabilities.contains(wl_seat::Capability::Keyboard) && state.keyboard.is_none() {
+                state.keyboard = Some(seat.get_keyboard(qh, ()));
+            }
+            if capabilities.contains(wl_seat::Capability::Pointer) && state.pointer.is_none() {
+                let pointer = seat.get_pointer(qh, ());
+                if let Some(gestures) = &state.pointer_gestures {
+                    gestures.get_swipe_gesture(&pointer, qh, ());
+                    gestures.get_pinch_gesture(&pointer, qh, ());
+                    gestures.get_hold_gesture(&pointer, qh, ());
+                }
+                if let Some(manager) = &state.relative_pointer_manager {
+                    manager.get_relative_pointer(&pointer, qh, ());
+                }
+                state.pointer = Some(pointer);
+            }
+            if capabilities.contains(wl_seat::Capability::Touch) && state.touch.is_none() {
+                state.touch = Some(seat.get_touch(qh, ()));
+            }
+            if let (Some(manager), None) = (&state.tablet_manager, &state.tablet_seat) {
+                state.tablet_seat = Some(manager.get_tablet_seat(seat, qh, ()));
+            }
+        }
+    }
+}
+
+impl Dispatch<WlKeyboard, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        _: &WlKeyboard,
+        event: wl_keyboard::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_keyboard::Event::Enter { surface, .. } => state.keyboard_focus = Some(surface),
+            wl_keyboard::Event::Leave { .. } => state.keyboard_focus = None,
+            wl_keyboard::Event::Key {
+                key,
+                state: WEnum::Value(wl_keyboard::KeyState::Pressed),
+                ..
+            } => state.pressed_keys.push(key),
+            wl_keyboard::Event::Keymap { .. } => state.keymaps += 1,
+            wl_keyboard::Event::RepeatInfo { rate, delay } => {
+                state.repeat_info = Some((rate, delay));
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlPointer, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        _: &WlPointer,
+        event: wl_pointer::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_pointer::Event::Enter { serial, .. } => state.pointer_enter_serial = Some(serial),
+            wl_pointer::Event::Button {
+                serial,
+                state: WEnum::Value(wl_pointer::ButtonState::Pressed),
+                ..
+            } => state.last_button_serial = Some(serial),
+            wl_pointer::Event::Axis {
+                axis: WEnum::Value(wl_pointer::Axis::VerticalScroll),
+                value,
+                ..
+            } => state.scrolled += value,
+            wl_pointer::Event::AxisStop {
+                axis: WEnum::Value(wl_pointer::Axis::VerticalScroll),
+                ..
+            } => state.scroll_stops += 1,
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlTouch, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        _: &WlTouch,
+        event: wl_touch::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let wl_touch::Event::Down {
+            serial,
+            surface,
+            x,
+            y,
+            ..
+        } = event
+        {
+            state.last_touch_serial = Some(serial);
+            state.touch_downs.push((surface, (x, y)));
+        }
+    }
+}
+
+impl Dispatch<ZwpRelativePointerV1, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        _: &ZwpRelativePointerV1,
+        event: zwp_relative_pointer_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let zwp_relative_pointer_v1::Event::RelativeMotion {
+            dx_unaccel,
+            dy_unaccel,
+            ..
+        } = event
+        {
+            state.relative_motions.push((dx_unaccel, dy_unaccel));
+        }
+    }
+}
+
+impl Dispatch<ZwpPointerGestureSwipeV1, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        _: &ZwpPointerGestureSwipeV1,
+        event: zwp_pointer_gesture_swipe_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let event = match event {
+            zwp_pointer_gesture_swipe_v1::Event::Begin { fingers, .. } => {
+                GestureEvent::SwipeBegin { fingers }
+            }
+            zwp_pointer_gesture_swipe_v1::Event::Update { dx, dy, .. } => {
+                GestureEvent::SwipeUpdate { dx, dy }
+            }
+            zwp_pointer_gesture_swipe_v1::Event::End { cancelled, .. } => GestureEvent::SwipeEnd {
+                cancelled: cancelled != 0,
+            },
+            _ => return,
+        };
+        state.gestures.push(event);
+    }
+}
+
+impl Dispatch<ZwpPointerGesturePinchV1, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        _: &ZwpPointerGesturePinchV1,
+        event: zwp_pointer_gesture_pinch_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let event = match event {
+            zwp_pointer_gesture_pinch_v1::Event::Begin { fingers, .. } => {
+                GestureEvent::PinchBegin { fingers }
+            }
+            zwp_pointer_gesture_pinch_v1::Event::Update { scale, .. } => {
+                GestureEvent::PinchUpdate { scale }
+            }
+            zwp_pointer_gesture_pinch_v1::Event::End { cancelled, .. } => GestureEvent::PinchEnd {
+                cancelled: cancelled != 0,
+            },
+            _ => return,
+        };
+        state.gestures.push(event);
+    }
+}
+
+impl Dispatch<ZwpPointerGestureHoldV1, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        _: &ZwpPointerGestureHoldV1,
+        event: zwp_pointer_gesture_hold_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let event = match event {
+            zwp_pointer_gesture_hold_v1::Event::Begin { fingers, .. } => {
+                GestureEvent::HoldBegin { fingers }
+            }
+            zwp_pointer_gesture_hold_v1::Event::End { cancelled, .. } => GestureEvent::HoldEnd {
+                cancelled: cancelled != 0,
+            },
+            _ => return,
+        };
+        state.gestures.push(event);
+    }
+}
+
+impl Dispatch<ZwpTabletSeatV2, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        _: &ZwpTabletSeatV2,
+        event: zwp_tablet_seat_v2::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwp_tablet_seat_v2::Event::TabletAdded { .. } => state.tablets += 1,
+            zwp_tablet_seat_v2::Event::ToolAdded { .. } => state.tablet_tools += 1,
+            _ => {}
+        }
+    }
+
+    event_created_child!(ClientState, ZwpTabletSeatV2, [
+        zwp_tablet_seat_v2::EVT_TABLET_ADDED_OPCODE => (ZwpTabletV2, ()),
+        zwp_tablet_seat_v2::EVT_TOOL_ADDED_OPCODE => (ZwpTabletToolV2, ()),
+    ]);
+}
+
+impl Dispatch<ZwpTabletToolV2, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        _: &ZwpTabletToolV2,
+        event: zwp_tablet_tool_v2::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let event = match event {
+            zwp_tablet_tool_v2::Event::ProximityIn { surface, .. } => {
+                TabletEvent::ProximityIn(surface)
+            }
+            zwp_tablet_tool_v2::Event::ProximityOut => TabletEvent::ProximityOut,
+            zwp_tablet_tool_v2::Event::Down { .. } => TabletEvent::Down,
+            zwp_tablet_tool_v2::Event::Up => TabletEvent::Up,
+            zwp_tablet_tool_v2::Event::Motion { x, y } => TabletEvent::Motion { x, y },
+            zwp_tablet_tool_v2::Event::Button {
+                button, state: s, ..
+            } => TabletEvent::Button {
+                button,
+                pressed: s == WEnum::Value(zwp_tablet_tool_v2::ButtonState::Pressed),
+            },
+            _ => return,
+        };
+        state.tablet_events.push(event);
+    }
+}
+
+impl Dispatch<XdgWmBase, ()> for ClientState {
+    fn event(
+        _: &mut Self,
+        wm_base: &XdgWmBase,
+        event: xdg_wm_base::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let xdg_wm_base::Event::Ping { serial } = event {
+            wm_base.pong(serial);
+        }
+    }
+}
+
+impl Dispatch<XdgSurface, Role> for ClientState {
+    fn event(
+        state: &mut Self,
+        xdg_surface: &XdgSurface,
+        event: xdg_surface::Event,
+        role: &Role,
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        let xdg_surface::Event::Configure { serial } = event else {
+            return;
+        };
+        xdg_surface.ack_configure(serial);
+
+        // Like a well-behaved client, draw at the configured size right away.
+        let (surface, size) = match *role {
+            Role::Toplevel(idx) => {
+                let window = &mut state.windows[idx];
+                let mut configure = std::mem::take(&mut window.pending);
+                configure.serial = serial;
+
+                let size = match configure.size {
+                    (0, _) | (_, 0) => DEFAULT_WINDOW_SIZE,
+                    size => size,
+                };
+                window.configures.push(configure);
+                (window.surface.clone(), size)
+            }
+            Role::Popup(idx) => {
+                let popup = &mut state.popups[idx];
+                popup.configured = true;
+                (popup.surface.clone(), popup.size)
+            }
+        };
+
+        let buffer = state.create_buffer(size, qh);
+        surface.attach(Some(&buffer), 0, 0);
+        surface.damage_buffer(0, 0, size.0, size.1);
+        surface.commit();
+    }
+}
+
+impl Dispatch<XdgToplevel, usize> for ClientState {
+    fn event(
+        state: &mut Self,
+        _: &XdgToplevel,
+        event: xdg_toplevel::Event,
+        idx: &usize,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let window = &mut state.windows[*idx];
+        match event {
+            xdg_toplevel::Event::Configure {
+                width,
+                height,
+                states,
+            } => {
+                window.pending.size = (width, height);
+                window.pending.states = states
+                    .chunks_exact(4)
+                    .filter_map(|chunk| {
+                        let raw = u32::from_ne_bytes(chunk.try_into().unwrap());
+                        xdg_toplevel::State::try_from(raw).ok()
+                    })
+                    .collect();
+            }
+            xdg_toplevel::Event::Close => window.close_requested = true,
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ZxdgToplevelDecorationV1, usize> for ClientState {
+    fn event(
+        state: &mut Self,
+        _: &ZxdgToplevelDecorationV1,
+        event: zxdg_toplevel_decoration_v1::Event,
+        idx: &usize,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let zxdg_toplevel_decoration_v1::Event::Configure {
+            mode: WEnum::Value(mode),
+        } = event
+        {
+            state.windows[*idx].decoration_mode = Some(mode);
+        }
+    }
+}
+
+delegate_noop!(ClientState: WlCompositor);
+delegate_noop!(ClientState: ZxdgDecorationManagerV1);
+delegate_noop!(ClientState: WlShmPool);
+delegate_noop!(ClientState: XdgPositioner);
+delegate_noop!(ClientState: ZwpPointerGesturesV1);
+delegate_noop!(ClientState: ZwpRelativePointerManagerV1);
+delegate_noop!(ClientState: ZwpTabletManagerV2);
+delegate_noop!(ClientState: ignore ZwpTabletV2);
+delegate_noop!(ClientState: ignore WlShm);
+delegate_noop!(ClientState: ignore WlBuffer);
+delegate_noop!(ClientState: ignore WlOutput);
+delegate_noop!(ClientState: ignore XdgPopup);
@@ -0,0 +1,87 @@
+use smithay::{
+    backend::renderer::{element::Element, pixman::PixmanRenderer},
+    input::pointer::CursorImageStatus,
+    utils::{Physical, Rectangle},
+};
+
+use super::fixture::{Fixture, OUTPUT_SIZE};
+use crate::config::CluxConfig;
+
+/// Where the cursor elements are drawn on the fixture output.
+fn cursor_geometry(f: &Fixture) -> Vec<Rectangle<i32, Physical>> {
+    let mut renderer = PixmanRenderer::new().unwrap();
+    f.state
+        .cursor_render_elements(&mut renderer, &f.output)
+        .iter()
+        .map(|element| element.geometry(1.0.into()))
+        .collect()
+}
+
+#[test]
+fn client_cursor_is_drawn_at_its_hotspot() {
+    let (mut f, mut client, _) = Fixture::with_window((0, 0));
+
+    f.pointer_to(50.0, 50.0);
+    f.roundtrip(&mut client);
+    client.set_cursor(Some(((16, 16), (4, 6))));
+    f.roundtrip(&mut client);
+
+    assert!(matches!(
+        f.state.cursor_status,
+        CursorImageStatus::Surface(_)
+    ));
+    assert_eq!(
+        cursor_geometry(&f),
+        vec![Rectangle::new((46, 44).into(), (16, 16).into())]
+    );
+}
+
+#[test]
+fn hidden_cursor_is_not_drawn() {
+    let (mut f, mut client, _) = Fixture::with_window((0, 0));
+
+    f.pointer_to(50.0, 50.0);
+    f.roundtrip(&mut client);
+    client.set_cursor(None);
+    f.roundtrip(&mut client);
+
+    assert!(matches!(f.state.cursor_status, CursorImageStatus::Hidden));
+    assert!(cursor_geometry(&f).is_empty());
+}
+
+#[test]
+fn missing_theme_still_draws_a_cursor() {
+    let mut config = CluxConfig::default();
+    config.cursor.theme = "clux-test-no-such-theme".to_string();
+    let mut f = Fixture::with_config(config);
+
+    f.pointer_to(100.0, 200.0);
+
+    assert_eq!(
+        cursor_geometry(&f),
+        vec![Rectangle::new((100, 200).into(), (8, 8).into())]
+    );
+}
+
+#[test]
+fn tablet_tool_has_its_own_cursor() {
+    let mut config = CluxConfig::default();
+    config.cursor.theme = "clux-test-no-such-theme".to_string();
+    let mut f = Fixture::with_config(config);
+
+    f.pointer_to(100.0, 200.0);
+    f.add_tablet();
+    f.tablet_proximity_in(0.5, 0.5);
+
+    let center = (OUTPUT_SIZE.0 / 2, OUTPUT_SIZE.1 / 2);
+    assert_eq!(
+        cursor_geometry(&f),
+        vec![
+            Rectangle::new(center.into(), (8, 8).into()),
+            Rectangle::new((100, 200).into(), (8, 8).into()),
+        ]
+    );
+
+    f.tablet_proximity_out();
+    assert_eq!(cursor_geometry(&f).len(), 1);
+}
@@ -0,0 +1,204 @@
+use smithay::{backend::input::ButtonState, utils::Point};
+use wayland_protocols::xdg::{
+    decoration::zv1::client::zxdg_toplevel_decoration_v1::Mode, shell::client::xdg_toplevel,
+};
+
+use super::{
+    client::{TestClient, DEFAULT_WINDOW_SIZE},
+    fixture::{Fixture, BTN_LEFT, OUTPUT_SIZE},
+};
+use crate::decorations::DecorationPart;
+
+/// Height of the titlebar and width of the border by default.
+const TITLEBAR: f64 = 24.0;
+const BORDER: f64 = 2.0;
+/// Where decorated windows are placed, leaving room for their decorations at the corner.
+const PLACED: (f64, f64) = (BORDER, TITLEBAR + BORDER);
+
+/// Opens a window that leaves its decorations to the compositor.
+fn decorated_window(f: &mut Fixture, client: &mut TestClient) -> usize {
+    let idx = client.create_decorated_window(None);
+    f.roundtrip(client);
+    idx
+}
+
+/// Output position in the middle of the titlebar button number `idx`, counting from the right.
+fn button_position(idx: usize) -> (f64, f64) {
+    let right = PLACED.0 + DEFAULT_WINDOW_SIZE.0 as f64;
+    let x = right - TITLEBAR * (idx as f64 + 0.5);
+    (x, PLACED.1 - TITLEBAR / 2.0)
+}
+
+#[test]
+fn clients_get_the_decorations_they_ask_for() {
+    let mut f = Fixture::new();
+    let mut client = f.client();
+
+    let undecided = client.create_decorated_window(None);
+    let client_side = client.create_decorated_window(Some(Mode::ClientSide));
+    f.roundtrip(&mut client);
+
+    let windows = &client.state.windows;
+    assert_eq!(windows[undecided].decoration_mode, Some(Mode::ServerSide));
+    assert_eq!(windows[client_side].decoration_mode, Some(Mode::ClientSide));
+}
+
+#[test]
+fn config_picks_the_mode_of_undecided_clients() {
+    let mut f = Fixture::with_toml(
+        r#"[decorations]
+mode = "client"
+"#,
+    );
+    let mut client = f.client();
+
+    let undecided = client.create_decorated_window(None);
+    let server_side = client.create_decorated_window(Some(Mode::ServerSide));
+    f.roundtrip(&mut client);
+
+    let windows = &client.state.windows;
+    assert_eq!(windows[undecided].decoration_mode, Some(Mode::ClientSide));
+    assert_eq!(windows[server_side].decoration_mode, Some(Mode::ServerSide));
+}
+
+#[test]
+fn decorated_windows_are_placed_below_their_titlebar() {
+    let mut f = Fixture::new();
+    let mut client = f.client();
+    let idx = decorated_window(&mut f, &mut client);
+
+    assert_eq!(f.window_location(&client, idx), Point::from((2, 26)));
+    let (window, part) = f.state.decoration_under((150.0, 12.0).into()).unwrap();
+    assert_eq!(window, f.window(&client, idx));
+    assert_eq!(part, DecorationPart::Titlebar);
+}
+
+#[test]
+fn titlebar_drag_moves_the_window() {
+    let mut f = Fixture::new();
+    let mut client = f.client();
+    let idx = decorated_window(&mut f, &mut client);
+
+    f.pointer_to(150.0, PLACED.1 - TITLEBAR / 2.0);
+    f.button(BTN_LEFT, ButtonState::Pressed);
+    f.pointer_to(250.0, 150.0);
+    f.button(BTN_LEFT, ButtonState::Released);
+    f.roundtrip(&mut client);
+
+    assert_eq!(f.window_location(&client, idx), Point::from((102, 162)));
+    // The titlebar is not part of the client, which never sees the click.
+    assert_eq!(client.state.last_button_serial, None);
+    assert_eq!(f.state.focused_window(), Some(f.window(&client, idx)));
+}
+
+#[test]
+fn border_drag_resizes_from_that_edge() {
+    let mut f = Fixture::new();
+    let mut client = f.client();
+    let idx = decorated_window(&mut f, &mut client);
+
+    let right = PLACED.0 + DEFAULT_WINDOW_SIZE.0 as f64;
+    f.pointer_to(right + BORDER / 2.0, 50.0);
+    f.button(BTN_LEFT, ButtonState::Pressed);
+    f.pointer_to(right + 50.0, 80.0);
+    f.roundtrip(&mut client);
+
+    let configure = client.last_configure(idx);
+    assert_eq!(
+        configure.size,
+        (DEFAULT_WINDOW_SIZE.0 + 49, DEFAULT_WINDOW_SIZE.1)
+    );
+    assert!(configure.states.contains(&xdg_toplevel::State::Resizing));
+}
+
+#[test]
+fn close_button_asks_the_window_to_close() {
+    let mut f = Fixture::new();
+    let mut client = f.client();
+    let idx = decorated_window(&mut f, &mut client);
+
+    let (x, y) = button_position(0);
+    f.pointer_to(x, y);
+    f.click(BTN_LEFT);
+    f.roundtrip(&mut client);
+
+    assert!(client.state.windows[idx].close_requested);
+}
+
+#[test]
+fn buttons_released_elsewhere_do_nothing() {
+    let mut f = Fixture::new();
+    let mut client = f.client();
+    let idx = decorated_window(&mut f, &mut client);
+
+    let (x, y) = button_position(0);
+    f.pointer_to(x, y);
+    f.button(BTN_LEFT, ButtonState::Pressed);
+    f.roundtrip(&mut client);
+    assert!(!client.state.windows[idx].close_requested);
+
+    let (x, y) = button_position(1);
+    f.pointer_to(x, y);
+    f.button(BTN_LEFT, ButtonState::Released);
+    f.roundtrip(&mut client);
+
+    assert!(!client.state.windows[idx].close_requested);
+    assert!(!client
+        .last_configure(idx)
+        .states
+        .contains(&xdg_toplevel::State::Maximized));
+}
+
+#[test]
+fn maximize_button_leaves_room_for_the_titlebar() {
+    let mut f = Fixture::new();
+    let mut client = f.client();
+    let idx = decorated_window(&mut f, &mut client);
+
+    let (x, y) = button_position(1);
+    f.pointer_to(x, y);
+    f.click(BTN_LEFT);
+    f.roundtrip(&mut client);
+
+    let configure = client.last_configure(idx);
+    assert_eq!(
+        configure.size,
+        (OUTPUT_SIZE.0, OUTPUT_SIZE.1 - TITLEBAR as i32)
+    );
+    assert!(configure.states.contains(&xdg_toplevel::State::Maximized));
+    assert_eq!(
+        f.window_location(&client, idx),
+        Point::from((0, TITLEBAR as i32))
+    );
+}
+
+#[test]
+fn minimize_button_sends_the_window_below_the_others() {
+    let mut f = Fixture::new();
+    let mut client = f.client();
+    let other = client.create_window();
+    f.roundtrip(&mut client);
+    let idx = decorated_window(&mut f, &mut client);
+
+    let (x, y) = button_position(2);
+    f.pointer_to(x, y);
+    f.click(BTN_LEFT);
+    f.roundtrip(&mut client);
+
+    let bottom = f.state.space.elements().next().cloned();
+    assert_eq!(bottom, Some(f.window(&client, idx)));
+    assert_eq!(f.state.focused_window(), Some(f.window(&client, other)));
+}
+
+#[test]
+fn undecorated_windows_have_no_titlebar() {
+    let mut f = Fixture::new();
+    let mut client = f.client();
+    let idx = client.create_decorated_window(Some(Mode::ClientSide));
+    f.roundtrip(&mut client);
+
+    // With nothing to make room for, the window stays where it was mapped.
+    assert_eq!(f.window_location(&client, idx), Point::from((0, 0)));
+    let titlebar = (150.0, -TITLEBAR / 2.0);
+    assert_eq!(f.state.decoration_under(titlebar.into()), None);
+}
@@ -0,0 +1,39 @@
+use crate::config::{AccelProfile, CluxConfig, DeviceKind};
+
+#[test]
+fn device_entries_override_global_settings_in_order() {
+    let config: CluxConfig = toml::from_str(
+        r#"[input]
+tap = true
+natural_scroll = true
+accel_speed = 0.2
+
+[[input.devices]]
+type = "pointer"
+natural_scroll = false
+accel_profile = "flat"
+
+[[input.devices]]
+name = "Logitech G502"
+accel_speed = -0.5
+"#,
+    )
+    .unwrap();
+    let input = &config.input;
+
+    let touchpad = input.device_settings(
+        "SYNA Touchpad",
+        &[DeviceKind::Pointer, DeviceKind::Touchpad],
+    );
+    assert_eq!(touchpad.tap, Some(true));
+    assert_eq!(touchpad.natural_scroll, Some(false));
+    assert_eq!(touchpad.accel_speed, Some(0.2));
+
+    let mouse = input.device_settings("Logitech G502", &[DeviceKind::Pointer]);
+    assert_eq!(mouse.accel_profile, Some(AccelProfile::Flat));
+    assert_eq!(mouse.accel_speed, Some(-0.5));
+
+    let keyboard = input.device_settings("AT Keyboard", &[DeviceKind::Keyboard]);
+    assert_eq!(keyboard.natural_scroll, Some(true));
+    assert_eq!(keyboard.accel_profile, None);
+}
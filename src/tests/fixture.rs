@@ -0,0 +1,822 @@
+use std::{
+    io::{Read, Write},
+    os::unix::net::UnixStream,
+    path::PathBuf,
+    sync::Once,
+    time::Duration,
+};
+
+use smithay::{
+    backend::input::{
+        AbsolutePositionEvent, Axis, AxisRelativeDirection, AxisSource, ButtonState, Device,
+        DeviceCapability, Event, GestureBeginEvent, GestureEndEvent, GestureHoldBeginEvent,
+        GestureHoldEndEvent, GesturePinchBeginEvent, GesturePinchEndEvent, GesturePinchUpdateEvent,
+        GestureSwipeBeginEvent, GestureSwipeEndEvent, GestureSwipeUpdateEvent, InputBackend,
+        InputEvent, KeyState, KeyboardKeyEvent, Keycode, PointerAxisEvent, PointerButtonEvent,
+        PointerMotionAbsoluteEvent, PointerMotionEvent, ProximityState, TabletToolAxisEvent,
+        TabletToolButtonEvent, TabletToolCapabilities, TabletToolDescriptor, TabletToolEvent,
+        TabletToolProximityEvent, TabletToolTipEvent, TabletToolTipState, TabletToolType,
+        TouchCancelEvent, TouchDownEvent, TouchEvent, TouchFrameEvent, TouchMotionEvent, TouchSlot,
+        TouchUpEvent, UnusedEvent,
+    },
+    desktop::Window,
+    output::Output,
+    reexports::{
+        calloop::EventLoop,
+        wayland_server::{Display, Resource},
+    },
+    utils::{Logical, Point},
+};
+use wayland_client::Proxy;
+
+use super::client::TestClient;
+use crate::{
+    backend::headless,
+    config::CluxConfig,
+    ipc::protocol::{Request, Response},
+    Clux,
+};
+
+pub const OUTPUT_SIZE: (i32, i32) = (1920, 1080);
+
+// Key and button codes as defined in the Linux kernel's linux/input-event-codes.h header file.
+pub const KEY_ESC: u32 = 1;
+pub const KEY_TAB: u32 = 15;
+pub const KEY_Q: u32 = 16;
+pub const KEY_W: u32 = 17;
+pub const KEY_R: u32 = 19;
+pub const KEY_RIGHT: u32 = 106;
+pub const KEY_LEFTMETA: u32 = 125;
+pub const BTN_LEFT: u32 = 0x110;
+pub const BTN_RIGHT: u32 = 0x111;
+
+/// A compositor running on a virtual output, driven synchronously by the test.
+pub struct Fixture {
+    pub event_loop: EventLoop<'static, Clux>,
+    pub state: Clux,
+    pub output: Output,
+    time: u64,
+}
+
+impl Fixture {
+    pub fn new() -> Self {
+        Self::with_config(CluxConfig::default())
+    }
+
+    pub fn with_config(config: CluxConfig) -> Self {
+        init_runtime_dir();
+
+        let mut event_loop = EventLoop::try_new().unwrap();
+        let display = Display::new().unwrap();
+        let mut state = Clux::with_config(&mut event_loop, display, config);
+
+        let output = headless::create_output(&mut state, "HEADLESS-1", OUTPUT_SIZE);
+        state.map_output(&output, (0, 0).into());
+
+        Self {
+            event_loop,
+            state,
+            output,
+            time: 0,
+        }
+    }
+
+    /// Parses `config` as the contents of a config.toml.
+    pub fn with_toml(config: &str) -> Self {
+        Self::with_config(toml::from_str(config).unwrap())
+    }
+
+    /// A compositor with a client that has one window, mapped at `location` on the canvas.
+    pub fn with_window(location: (i32, i32)) -> (Self, TestClient, usize) {
+        let mut f = Self::new();
+        let mut client = f.client();
+        let idx = f.open_window(&mut client, location);
+        (f, client, idx)
+    }
+
+    /// Connects a client, which has bound the globals and the seat's devices once it is returned.
+    pub fn client(&mut self) -> TestClient {
+        let path = runtime_dir().join(&self.state.socket_name);
+        let mut client = TestClient::connect(UnixStream::connect(path).unwrap());
+        self.roundtrip(&mut client);
+        client
+    }
+
+    /// Has `client` open a window and moves it to `location` once it is mapped.
+    pub fn open_window(&mut self, client: &mut TestClient, location: (i32, i32)) -> usize {
+        let idx = client.create_window();
+        self.roundtrip(client);
+
+        let window = self.window(client, idx);
+        self.state.space.map_element(window, location, false);
+        idx
+    }
+
+    /// Runs one iteration of the compositor, the way the backends do between frames.
+    pub fn dispatch(&mut self) {
+        self.event_loop
+            .dispatch(Some(Duration::ZERO), &mut self.state)
+            .unwrap();
+        self.state.refresh_space();
+        self.state.popups.cleanup();
+        self.state.display_handle.flush_clients().unwrap();
+    }
+
+    /// Exchanges messages until neither side has anything left to say.
+    pub fn roundtrip(&mut self, client: &mut TestClient) {
+        for _ in 0..8 {
+            client.flush();
+            self.dispatch();
+            client.dispatch();
+        }
+    }
+
+    /// Returns the server side window of `client`'s window number `idx`.
+    pub fn window(&self, client: &TestClient, idx: usize) -> Window {
+        let id = client.state.windows[idx].surface.id().protocol_id();
+        self.state
+            .space
+            .elements()
+            .find(|w| w.toplevel().unwrap().wl_surface().id().protocol_id() == id)
+            .cloned()
+            .expect("window is not mapped")
+    }
+
+    pub fn window_location(&self, client: &TestClient, idx: usize) -> Point<i32, Logical> {
+        let window = self.window(client, idx);
+        self.state.space.element_location(&window).unwrap()
+    }
+
+    /// Opens a fresh connection to the control socket.
+    pub fn ipc_connect(&self) -> UnixStream {
+        UnixStream::connect(&self.state.ipc.as_ref().unwrap().path).unwrap()
+    }
+
+    /// Sends one raw line over the control socket and returns the reply line.
+    pub fn ipc_raw(&mut self, line: &str) -> String {
+        let mut stream = self.ipc_connect();
+        stream.write_all(line.as_bytes()).unwrap();
+        stream.write_all(b"\n").unwrap();
+        stream.set_nonblocking(true).unwrap();
+
+        let mut reply = Vec::new();
+        for _ in 0..8 {
+            self.dispatch();
+
+            let mut buf = [0u8; 4096];
+            while let Ok(n) = stream.read(&mut buf) {
+                if n == 0 {
+                    break;
+                }
+                reply.extend_from_slice(&buf[..n]);
+            }
+            if reply.ends_with(b"\n") {
+                break;
+            }
+        }
+
+        String::from_utf8(reply).unwrap()
+    }
+
+    pub fn ipc(&mut self, request: &Request) -> Response {
+        let reply = self.ipc_raw(&serde_json::to_string(request).unwrap());
+        serde_json::from_str(&reply).unwrap()
+    }
+
+    pub fn input(&mut self, event: InputEvent<TestInput>) {
+        self.state.process_input_event(event);
+        self.dispatch();
+    }
+
+    /// An event happening now, to fill in with whatever the event at hand is about.
+    fn event(&mut self) -> TestEvent {
+        self.time += 10_000;
+        TestEvent {
+            device: TestDevice::default(),
+            time: self.time,
+            key: 0,
+            key_state: KeyState::Released,
+            button: 0,
+            button_state: ButtonState::Released,
+            x: 0.0,
+            y: 0.0,
+            fraction: false,
+            delta: (0.0, 0.0),
+            v120: 0.0,
+            finger_scroll: None,
+            fingers: 0,
+            scale: 1.0,
+            cancelled: false,
+            slot: TouchSlot::from(None::<u32>),
+            proximity: ProximityState::In,
+            tip: TabletToolTipState::Up,
+        }
+    }
+
+    pub fn key(&mut self, key: u32, key_state: KeyState) {
+        let event = TestEvent {
+            key,
+            key_state,
+            ..self.event()
+        };
+        self.input(InputEvent::Keyboard { event });
+    }
+
+    /// Presses or releases `key` on the keyboard called `device` rather than the test device.
+    pub fn key_on(&mut self, device: &'static str, key: u32, key_state: KeyState) {
+        let event = TestEvent {
+            device: TestDevice(device),
+            key,
+            key_state,
+            ..self.event()
+        };
+        self.input(InputEvent::Keyboard { event });
+    }
+
+    /// Presses `keys` in order, then releases them in reverse order.
+    pub fn press_combo(&mut self, keys: &[u32]) {
+        for key in keys {
+            self.key(*key, KeyState::Pressed);
+        }
+        for key in keys.iter().rev() {
+            self.key(*key, KeyState::Released);
+        }
+    }
+
+    /// Moves the pointer to an output-local position.
+    pub fn pointer_to(&mut self, x: f64, y: f64) {
+        let event = TestEvent {
+            x,
+            y,
+            ..self.event()
+        };
+        self.input(InputEvent::PointerMotionAbsolute { event });
+    }
+
+    /// Moves the pointer the way a mouse does, by a delta in screen pixels.
+    pub fn pointer_by(&mut self, dx: f64, dy: f64) {
+        let event = TestEvent {
+            delta: (dx, dy),
+            ..self.event()
+        };
+        self.input(InputEvent::PointerMotion { event });
+    }
+
+    pub fn button(&mut self, button: u32, button_state: ButtonState) {
+        let event = TestEvent {
+            button,
+            button_state,
+            ..self.event()
+        };
+        self.input(InputEvent::PointerButton { event });
+    }
+
+    /// Turns a mouse wheel by `notches`, positive values scrolling down.
+    pub fn scroll(&mut self, notches: f64) {
+        let event = TestEvent {
+            v120: notches * 120.0,
+            ..self.event()
+        };
+        self.input(InputEvent::PointerAxis { event });
+    }
+
+    /// Scrolls vertically on a touchpad by `pixels`; zero ends the scroll.
+    pub fn finger_scroll(&mut self, pixels: f64) {
+        let event = TestEvent {
+            finger_scroll: Some(pixels),
+            ..self.event()
+        };
+        self.input(InputEvent::PointerAxis { event });
+    }
+
+    pub fn click(&mut self, button: u32) {
+        self.button(button, ButtonState::Pressed);
+        self.button(button, ButtonState::Released);
+    }
+
+    pub fn swipe_begin(&mut self, fingers: u32) {
+        let event = TestEvent {
+            fingers,
+            ..self.event()
+        };
+        self.input(InputEvent::GestureSwipeBegin { event });
+    }
+
+    pub fn swipe_update(&mut self, dx: f64, dy: f64) {
+        let event = TestEvent {
+            delta: (dx, dy),
+            ..self.event()
+        };
+        self.input(InputEvent::GestureSwipeUpdate { event });
+    }
+
+    pub fn swipe_end(&mut self, cancelled: bool) {
+        let event = TestEvent {
+            cancelled,
+            ..self.event()
+        };
+        self.input(InputEvent::GestureSwipeEnd { event });
+    }
+
+    pub fn pinch_begin(&mut self, fingers: u32) {
+        let event = TestEvent {
+            fingers,
+            ..self.event()
+        };
+        self.input(InputEvent::GesturePinchBegin { event });
+    }
+
+    /// Updates the pinch to `scale` times the distance the fingers started at.
+    pub fn pinch_update(&mut self, scale: f64) {
+        let event = TestEvent {
+            scale,
+            ..self.event()
+        };
+        self.input(InputEvent::GesturePinchUpdate { event });
+    }
+
+    pub fn pinch_end(&mut self, cancelled: bool) {
+        let event = TestEvent {
+            cancelled,
+            ..self.event()
+        };
+        self.input(InputEvent::GesturePinchEnd { event });
+    }
+
+    /// Rests `fingers` on the touchpad and lifts them again.
+    pub fn hold(&mut self, fingers: u32, cancelled: bool) {
+        let event = TestEvent {
+            fingers,
+            ..self.event()
+        };
+        self.input(InputEvent::GestureHoldBegin { event });
+        let event = TestEvent {
+            cancelled,
+            ..self.event()
+        };
+        self.input(InputEvent::GestureHoldEnd { event });
+    }
+
+    /// Plugs in a tablet; the test device counts as one.
+    pub fn add_tablet(&mut self) {
+        let device = TestDevice::default();
+        self.input(InputEvent::DeviceAdded { device });
+    }
+
+    /// A tablet event at `x` and `y`, as fractions of the tablet's width and height.
+    fn tablet_event(&mut self, x: f64, y: f64) -> TestEvent {
+        TestEvent {
+            x,
+            y,
+            fraction: true,
+            ..self.event()
+        }
+    }
+
+    /// Brings the pen close to the tablet at `x` and `y`, as fractions of the tablet's width and
+    /// height.
+    pub fn tablet_proximity_in(&mut self, x: f64, y: f64) {
+        let event = self.tablet_event(x, y);
+        self.input(InputEvent::TabletToolProximity { event });
+    }
+
+    pub fn tablet_proximity_out(&mut self) {
+        let event = TestEvent {
+            proximity: ProximityState::Out,
+            ..self.tablet_event(0.0, 0.0)
+        };
+        self.input(InputEvent::TabletToolProximity { event });
+    }
+
+    pub fn tablet_motion(&mut self, x: f64, y: f64) {
+        let event = self.tablet_event(x, y);
+        self.input(InputEvent::TabletToolAxis { event });
+    }
+
+    pub fn tablet_tip(&mut self, tip: TabletToolTipState) {
+        let event = TestEvent {
+            tip,
+            ..self.tablet_event(0.0, 0.0)
+        };
+        self.input(InputEvent::TabletToolTip { event });
+    }
+
+    pub fn tablet_button(&mut self, button: u32, button_state: ButtonState) {
+        let event = TestEvent {
+            button,
+            button_state,
+            ..self.tablet_event(0.0, 0.0)
+        };
+        self.input(InputEvent::TabletToolButton { event });
+    }
+
+    fn touch_event(&mut self, slot: u32, x: f64, y: f64) -> TestEvent {
+        TestEvent {
+            slot: Some(slot).into(),
+            x,
+            y,
+            ..self.event()
+        }
+    }
+
+    /// Puts finger `slot` down at an output-local position, followed by a frame like
+    /// touchscreens send.
+    pub fn touch_down(&mut self, slot: u32, x: f64, y: f64) {
+        let event = self.touch_event(slot, x, y);
+        self.input(InputEvent::TouchDown { event });
+        self.touch_frame();
+    }
+
+    pub fn touch_motion(&mut self, slot: u32, x: f64, y: f64) {
+        let event = self.touch_event(slot, x, y);
+        self.input(InputEvent::TouchMotion { event });
+        self.touch_frame();
+    }
+
+    pub fn touch_up(&mut self, slot: u32) {
+        let event = self.touch_event(slot, 0.0, 0.0);
+        self.input(InputEvent::TouchUp { event });
+        self.touch_frame();
+    }
+
+    fn touch_frame(&mut self) {
+        let event = self.event();
+        self.input(InputEvent::TouchFrame { event });
+    }
+}
+
+fn runtime_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("clux-test-{}", std::process::id()))
+}
+
+/// Points `XDG_RUNTIME_DIR` at a private directory so tests never touch a real session.
+fn init_runtime_dir() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        let dir = runtime_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        std::env::set_var("XDG_RUNTIME_DIR", &dir);
+    });
+}
+
+/// Input backend for synthetic events injected by tests.
+#[derive(Debug)]
+pub struct TestInput;
+
+/// A device of the test backend by name, able to do anything.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TestDevice(&'static str);
+
+impl Default for TestDevice {
+    fn default() -> Self {
+        Self("Clux test device")
+    }
+}
+
+impl Device for TestDevice {
+    fn id(&self) -> String {
+        self.0.to_lowercase().replace(' ', "-")
+    }
+
+    fn name(&self) -> String {
+        self.0.into()
+    }
+
+    fn has_capability(&self, _capability: DeviceCapability) -> bool {
+        true
+    }
+
+    fn usb_id(&self) -> Option<(u32, u32)> {
+        None
+    }
+
+    fn syspath(&self) -> Option<PathBuf> {
+        None
+    }
+}
+
+impl InputBackend for TestInput {
+    type Device = TestDevice;
+    type KeyboardKeyEvent = TestEvent;
+    type PointerAxisEvent = TestEvent;
+    type PointerButtonEvent = TestEvent;
+    type PointerMotionEvent = TestEvent;
+    type PointerMotionAbsoluteEvent = TestEvent;
+    type GestureSwipeBeginEvent = TestEvent;
+    type GestureSwipeUpdateEvent = TestEvent;
+    type GestureSwipeEndEvent = TestEvent;
+    type GesturePinchBeginEvent = TestEvent;
+    type GesturePinchUpdateEvent = TestEvent;
+    type GesturePinchEndEvent = TestEvent;
+    type GestureHoldBeginEvent = TestEvent;
+    type GestureHoldEndEvent = TestEvent;
+    type TouchDownEvent = TestEvent;
+    type TouchUpEvent = TestEvent;
+    type TouchMotionEvent = TestEvent;
+    type TouchCancelEvent = TestEvent;
+    type TouchFrameEvent = TestEvent;
+    type TabletToolAxisEvent = TestEvent;
+    type TabletToolProximityEvent = TestEvent;
+    type TabletToolTipEvent = TestEvent;
+    type TabletToolButtonEvent = TestEvent;
+    type SwitchToggleEvent = UnusedEvent;
+    type SpecialEvent = UnusedEvent;
+}
+
+/// Tool of the test tablet: a pen reporting pressure.
+pub const TABLET_TOOL: TabletToolDescriptor = TabletToolDescriptor {
+    tool_type: TabletToolType::Pen,
+    hardware_serial: 1,
+    hardware_id_wacom: 0,
+    capabilities: TabletToolCapabilities::PRESSURE,
+};
+
+/// Any event of the test backend, each kind reading the fields it is about.
+pub struct TestEvent {
+    device: TestDevice,
+    time: u64,
+    /// Evdev code of a key.
+    key: u32,
+    key_state: KeyState,
+    /// Evdev code of a pointer or tablet tool button.
+    button: u32,
+    button_state: ButtonState,
+    /// Absolute position, in output-local logical pixels unless `fraction` is set.
+    x: f64,
+    y: f64,
+    /// Whether `x` and `y` are fractions of the device's width and height, as tablets report.
+    fraction: bool,
+    /// Relative motion of the pointer or of the fingers of a gesture.
+    delta: (f64, f64),
+    /// Vertical mouse wheel steps.
+    v120: f64,
+    /// Vertical touchpad scrolling in pixels, reported instead of wheel steps when set.
+    finger_scroll: Option<f64>,
+    fingers: u32,
+    scale: f64,
+    cancelled: bool,
+    slot: TouchSlot,
+    proximity: ProximityState,
+    tip: TabletToolTipState,
+}
+
+impl Event<TestInput> for TestEvent {
+    fn time(&self) -> u64 {
+        self.time
+    }
+
+    fn device(&self) -> TestDevice {
+        self.device.clone()
+    }
+}
+
+impl KeyboardKeyEvent<TestInput> for TestEvent {
+    fn key_code(&self) -> Keycode {
+        // xkb keycodes are offset by 8 from the evdev ones.
+        Keycode::new(self.key + 8)
+    }
+
+    fn state(&self) -> KeyState {
+        self.key_state
+    }
+
+    fn count(&self) -> u32 {
+        u32::from(self.key_state == KeyState::Pressed)
+    }
+}
+
+impl AbsolutePositionEvent<TestInput> for TestEvent {
+    fn x(&self) -> f64 {
+        self.x
+    }
+
+    fn y(&self) -> f64 {
+        self.y
+    }
+
+    fn x_transformed(&self, width: i32) -> f64 {
+        if self.fraction {
+            self.x * width as f64
+        } else {
+            self.x
+        }
+    }
+
+    fn y_transformed(&self, height: i32) -> f64 {
+        if self.fraction {
+            self.y * height as f64
+        } else {
+            self.y
+        }
+    }
+}
+
+impl PointerMotionEvent<TestInput> for TestEvent {
+    fn delta_x(&self) -> f64 {
+        self.delta.0
+    }
+
+    fn delta_y(&self) -> f64 {
+        self.delta.1
+    }
+
+    fn delta_x_unaccel(&self) -> f64 {
+        self.delta.0
+    }
+
+    fn delta_y_unaccel(&self) -> f64 {
+        self.delta.1
+    }
+}
+
+impl PointerButtonEvent<TestInput> for TestEvent {
+    fn button_code(&self) -> u32 {
+        self.button
+    }
+
+    fn state(&self) -> ButtonState {
+        self.button_state
+    }
+}
+
+impl PointerAxisEvent<TestInput> for TestEvent {
+    fn amount(&self, axis: Axis) -> Option<f64> {
+        self.finger_scroll.filter(|_| axis == Axis::Vertical)
+    }
+
+    fn amount_v120(&self, axis: Axis) -> Option<f64> {
+        let wheel = axis == Axis::Vertical && self.finger_scroll.is_none();
+        wheel.then_some(self.v120)
+    }
+
+    fn source(&self) -> AxisSource {
+        match self.finger_scroll {
+            Some(_) => AxisSource::Finger,
+            None => AxisSource::Wheel,
+        }
+    }
+
+    fn relative_direction(&self, _axis: Axis) -> AxisRelativeDirection {
+        AxisRelativeDirection::Identical
+    }
+}
+
+impl GestureBeginEvent<TestInput> for TestEvent {
+    fn fingers(&self) -> u32 {
+        self.fingers
+    }
+}
+
+impl GestureEndEvent<TestInput> for TestEvent {
+    fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+}
+
+impl GestureSwipeUpdateEvent<TestInput> for TestEvent {
+    fn delta_x(&self) -> f64 {
+        self.delta.0
+    }
+
+    fn delta_y(&self) -> f64 {
+        self.delta.1
+    }
+}
+
+impl GesturePinchUpdateEvent<TestInput> for TestEvent {
+    fn delta_x(&self) -> f64 {
+        self.delta.0
+    }
+
+    fn delta_y(&self) -> f64 {
+        self.delta.1
+    }
+
+    fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    fn rotation(&self) -> f64 {
+        0.0
+    }
+}
+
+impl TouchEvent<TestInput> for TestEvent {
+    fn slot(&self) -> TouchSlot {
+        self.slot
+    }
+}
+
+impl TabletToolEvent<TestInput> for TestEvent {
+    fn tool(&self) -> TabletToolDescriptor {
+        TABLET_TOOL
+    }
+
+    fn delta_x(&self) -> f64 {
+        0.0
+    }
+
+    fn delta_y(&self) -> f64 {
+        0.0
+    }
+
+    fn distance(&self) -> f64 {
+        0.0
+    }
+
+    fn distance_has_changed(&self) -> bool {
+        false
+    }
+
+    fn pressure(&self) -> f64 {
+        0.0
+    }
+
+    fn pressure_has_changed(&self) -> bool {
+        false
+    }
+
+    fn slider_position(&self) -> f64 {
+        0.0
+    }
+
+    fn slider_has_changed(&self) -> bool {
+        false
+    }
+
+    fn tilt_x(&self) -> f64 {
+        0.0
+    }
+
+    fn tilt_x_has_changed(&self) -> bool {
+        false
+    }
+
+    fn tilt_y(&self) -> f64 {
+        0.0
+    }
+
+    fn tilt_y_has_changed(&self) -> bool {
+        false
+    }
+
+    fn rotation(&self) -> f64 {
+        0.0
+    }
+
+    fn rotation_has_changed(&self) -> bool {
+        false
+    }
+
+    fn wheel_delta(&self) -> f64 {
+        0.0
+    }
+
+    fn wheel_delta_discrete(&self) -> i32 {
+        0
+    }
+
+    fn wheel_has_changed(&self) -> bool {
+        false
+    }
+}
+
+impl TabletToolProximityEvent<TestInput> for TestEvent {
+    fn state(&self) -> ProximityState {
+        self.proximity
+    }
+}
+
+impl TabletToolTipEvent<TestInput> for TestEvent {
+    fn tip_state(&self) -> TabletToolTipState {
+        self.tip
+    }
+}
+
+impl TabletToolButtonEvent<TestInput> for TestEvent {
+    fn button(&self) -> u32 {
+        self.button
+    }
+
+    fn seat_button_count(&self) -> u32 {
+        u32::from(self.button_state == ButtonState::Pressed)
+    }
+
+    fn button_state(&self) -> ButtonState {
+        self.button_state
+    }
+}
+
+impl PointerMotionAbsoluteEvent<TestInput> for TestEvent {}
+impl GestureSwipeBeginEvent<TestInput> for TestEvent {}
+impl GestureSwipeEndEvent<TestInput> for TestEvent {}
+impl GesturePinchBeginEvent<TestInput> for TestEvent {}
+impl GesturePinchEndEvent<TestInput> for TestEvent {}
+impl GestureHoldBeginEvent<TestInput> for TestEvent {}
+impl GestureHoldEndEvent<TestInput> for TestEvent {}
+impl TouchDownEvent<TestInput> for TestEvent {}
+impl TouchMotionEvent<TestInput> for TestEvent {}
+impl TouchUpEvent<TestInput> for TestEvent {}
+impl TouchCancelEvent<TestInput> for TestEvent {}
+impl TouchFrameEvent<TestInput> for TestEvent {}
+impl TabletToolAxisEvent<TestInput> for TestEvent {}
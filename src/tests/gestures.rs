@@ -0,0 +1,250 @@
+use smithay::backend::input::{ButtonState, KeyState};
+
+use super::{
+    client::{GestureEvent, TestClient},
+    fixture::{Fixture, BTN_LEFT, KEY_LEFTMETA},
+};
+
+const GESTURE_CONFIG: &str = r#"
+[keybindings.pan_left]
+combo = "swipe3_left"
+action = "pan_left"
+
+[keybindings.zoom_in]
+combo = "pinch_out"
+action = "zoom_in"
+
+[keybindings.overview]
+combo = "hold3"
+action = "toggle_overview"
+
+[keybindings.overview_swipe]
+combo = "logo+swipe4_up"
+action = "toggle_overview"
+"#;
+
+#[test]
+fn pan_swipe_pans_the_way_its_action_does() {
+    let mut f = Fixture::with_toml(GESTURE_CONFIG);
+    let before = f.state.viewport(&f.output).origin;
+
+    f.swipe_begin(3);
+    f.swipe_update(-10.0, 0.0);
+    // Still too short to tell the direction.
+    assert_eq!(f.state.viewport(&f.output).origin, before);
+
+    // Swiping left pans left, like pressing the keys bound to `pan_left` does.
+    f.swipe_update(-20.0, 2.0);
+    assert_eq!(f.state.viewport(&f.output).origin.x, before.x - 30.0);
+
+    f.swipe_update(-10.0, 0.0);
+    f.swipe_update(5.0, 0.0);
+    f.swipe_end(false);
+    assert_eq!(f.state.viewport(&f.output).origin.x, before.x - 35.0);
+}
+
+#[test]
+fn unbound_swipe_leaves_the_canvas_alone() {
+    let mut f = Fixture::with_toml(GESTURE_CONFIG);
+    let before = f.state.viewport(&f.output);
+
+    f.swipe_begin(3);
+    f.swipe_update(50.0, 0.0);
+    f.swipe_end(false);
+
+    assert_eq!(f.state.viewport(&f.output).origin, before.origin);
+    assert!(f.state.overview.is_none());
+}
+
+#[test]
+fn pinch_zooms_along_with_the_fingers() {
+    let mut f = Fixture::with_toml(GESTURE_CONFIG);
+
+    f.pinch_begin(2);
+    f.pinch_update(1.1);
+    assert_eq!(f.state.viewport(&f.output).zoom, 1.0);
+
+    f.pinch_update(1.2);
+    assert!((f.state.viewport(&f.output).zoom - 1.2).abs() < 1e-9);
+
+    f.pinch_update(1.5);
+    f.pinch_end(false);
+    assert!((f.state.viewport(&f.output).zoom - 1.5).abs() < 1e-9);
+}
+
+#[test]
+fn pinch_bound_to_zoom_out_never_zooms_in() {
+    let mut f = Fixture::with_toml(
+        r#"
+[keybindings.spread_out]
+combo = "pinch_out"
+action = "zoom_out"
+"#,
+    );
+
+    f.pinch_begin(2);
+    f.pinch_update(1.25);
+    assert!((f.state.viewport(&f.output).zoom - 0.8).abs() < 1e-9);
+
+    f.pinch_update(1.5625);
+    f.pinch_end(false);
+    assert!((f.state.viewport(&f.output).zoom - 0.64).abs() < 1e-9);
+}
+
+#[test]
+fn hold_runs_its_binding_unless_cancelled() {
+    let mut f = Fixture::with_toml(GESTURE_CONFIG);
+
+    f.hold(3, true);
+    assert!(f.state.overview.is_none());
+
+    f.hold(3, false);
+    assert!(f.state.overview.is_some());
+}
+
+#[test]
+fn one_shot_swipe_runs_once() {
+    let mut f = Fixture::with_toml(GESTURE_CONFIG);
+
+    f.key(KEY_LEFTMETA, KeyState::Pressed);
+    f.swipe_begin(4);
+    for _ in 0..20 {
+        f.swipe_update(0.0, -20.0);
+    }
+    assert!(f.state.overview.is_some());
+    f.swipe_end(false);
+    f.key(KEY_LEFTMETA, KeyState::Released);
+
+    // Running the toggle a second time would have closed the overview again.
+    assert!(f.state.overview.is_some());
+}
+
+/// Connects a client with a window under the pointer, so it has pointer focus.
+fn focused_client(f: &mut Fixture) -> TestClient {
+    let mut client = f.client();
+    f.open_window(&mut client, (0, 0));
+
+    f.pointer_to(50.0, 50.0);
+    f.roundtrip(&mut client);
+    client
+}
+
+#[test]
+fn unbound_swipe_reaches_the_client() {
+    let mut f = Fixture::with_toml(GESTURE_CONFIG);
+    let mut client = focused_client(&mut f);
+
+    // Only bound with logo held, so the client gets the whole swipe.
+    f.swipe_begin(4);
+    f.swipe_update(5.0, 0.0);
+    f.swipe_end(false);
+    f.roundtrip(&mut client);
+
+    assert_eq!(
+        client.state.gestures,
+        vec![
+            GestureEvent::SwipeBegin { fingers: 4 },
+            GestureEvent::SwipeUpdate { dx: 5.0, dy: 0.0 },
+            GestureEvent::SwipeEnd { cancelled: false },
+        ]
+    );
+}
+
+#[test]
+fn swipe_is_replayed_once_its_direction_is_unbound() {
+    let mut f = Fixture::with_toml(GESTURE_CONFIG);
+    let mut client = focused_client(&mut f);
+    let before = f.state.viewport(&f.output).origin;
+
+    f.swipe_begin(3);
+    f.swipe_update(10.0, 0.0);
+    f.roundtrip(&mut client);
+    // It could still turn out to be the bound swipe3_left.
+    assert!(client.state.gestures.is_empty());
+
+    f.swipe_update(10.0, 0.0);
+    f.swipe_update(5.0, 0.0);
+    f.swipe_end(true);
+    f.roundtrip(&mut client);
+
+    assert_eq!(
+        client.state.gestures,
+        vec![
+            GestureEvent::SwipeBegin { fingers: 3 },
+            GestureEvent::SwipeUpdate { dx: 20.0, dy: 0.0 },
+            GestureEvent::SwipeUpdate { dx: 5.0, dy: 0.0 },
+            GestureEvent::SwipeEnd { cancelled: true },
+        ]
+    );
+    assert_eq!(f.state.viewport(&f.output).origin, before);
+}
+
+#[test]
+fn bound_pinch_is_kept_from_the_client() {
+    let mut f = Fixture::with_toml(GESTURE_CONFIG);
+    let mut client = focused_client(&mut f);
+
+    f.pinch_begin(2);
+    f.pinch_update(1.5);
+    f.pinch_end(false);
+    f.roundtrip(&mut client);
+    assert!(client.state.gestures.is_empty());
+
+    // Pinching in is not bound.
+    f.pinch_begin(2);
+    f.pinch_update(0.75);
+    f.pinch_end(false);
+    f.roundtrip(&mut client);
+
+    assert_eq!(
+        client.state.gestures,
+        vec![
+            GestureEvent::PinchBegin { fingers: 2 },
+            GestureEvent::PinchUpdate { scale: 0.75 },
+            GestureEvent::PinchEnd { cancelled: false },
+        ]
+    );
+}
+
+#[test]
+fn unbound_hold_reaches_the_client() {
+    let mut f = Fixture::with_toml(GESTURE_CONFIG);
+    let mut client = focused_client(&mut f);
+
+    f.hold(4, true);
+    f.hold(3, false);
+    f.roundtrip(&mut client);
+
+    assert!(f.state.overview.is_some());
+    assert_eq!(
+        client.state.gestures,
+        vec![
+            GestureEvent::HoldBegin { fingers: 4 },
+            GestureEvent::HoldEnd { cancelled: true },
+        ]
+    );
+}
+
+#[test]
+fn gestures_reach_the_client_during_a_grab() {
+    let mut f = Fixture::with_toml(GESTURE_CONFIG);
+    let mut client = focused_client(&mut f);
+
+    f.button(BTN_LEFT, ButtonState::Pressed);
+    f.roundtrip(&mut client);
+    client.start_move(0);
+    f.roundtrip(&mut client);
+
+    f.swipe_begin(4);
+    f.swipe_end(false);
+    f.button(BTN_LEFT, ButtonState::Released);
+    f.roundtrip(&mut client);
+
+    assert_eq!(
+        client.state.gestures,
+        vec![
+            GestureEvent::SwipeBegin { fingers: 4 },
+            GestureEvent::SwipeEnd { cancelled: false },
+        ]
+    );
+}
@@ -0,0 +1,337 @@
+use smithay::{
+    backend::input::{ButtonState, KeyState},
+    utils::Point,
+};
+use wayland_protocols::xdg::shell::client::xdg_toplevel;
+
+use super::fixture::{
+    Fixture, BTN_LEFT, BTN_RIGHT, KEY_ESC, KEY_LEFTMETA, KEY_Q, KEY_R, KEY_RIGHT, KEY_TAB, KEY_W,
+};
+use crate::ipc::protocol::{Reply, Request, Response};
+
+const OVERVIEW_CONFIG: &str = r#"
+[keybindings.overview]
+combo = "logo+tab"
+action = "toggle_overview"
+"#;
+
+#[test]
+fn click_focuses_and_raises() {
+    let (mut f, mut client, bottom) = Fixture::with_window((400, 0));
+    let top = client.create_window();
+    f.roundtrip(&mut client);
+    let window = f.window(&client, bottom);
+
+    f.pointer_to(450.0, 50.0);
+    f.click(BTN_LEFT);
+    f.roundtrip(&mut client);
+
+    assert_eq!(
+        client.state.keyboard_focus.as_ref(),
+        Some(&client.state.windows[bottom].surface)
+    );
+    assert_eq!(f.state.space.elements().last(), Some(&window));
+
+    f.pointer_to(50.0, 50.0);
+    f.click(BTN_LEFT);
+    f.roundtrip(&mut client);
+
+    assert_eq!(
+        client.state.keyboard_focus.as_ref(),
+        Some(&client.state.windows[top].surface)
+    );
+}
+
+#[test]
+fn keybinding_toggles_overview() {
+    let mut f = Fixture::with_toml(OVERVIEW_CONFIG);
+
+    f.press_combo(&[KEY_LEFTMETA, KEY_TAB]);
+    assert!(f.state.overview.is_some());
+
+    f.press_combo(&[KEY_LEFTMETA, KEY_TAB]);
+    assert!(f.state.overview.is_none());
+}
+
+#[test]
+fn overview_click_focuses_window_without_reaching_client() {
+    let mut f = Fixture::with_toml(OVERVIEW_CONFIG);
+    let mut client = f.client();
+    let idx = f.open_window(&mut client, (0, 0));
+
+    f.press_combo(&[KEY_LEFTMETA, KEY_TAB]);
+    let thumbnail = f.state.overview_layout(&f.output)[0].geometry;
+    let center = thumbnail.loc + thumbnail.size.downscale(2.0).to_point();
+
+    f.pointer_to(center.x, center.y);
+    f.click(BTN_LEFT);
+    f.roundtrip(&mut client);
+
+    assert!(f.state.overview.is_none());
+    assert_eq!(client.state.last_button_serial, None);
+    assert_eq!(
+        client.state.keyboard_focus.as_ref(),
+        Some(&client.state.windows[idx].surface)
+    );
+}
+
+#[test]
+fn move_request_starts_move_grab() {
+    let (mut f, mut client, idx) = Fixture::with_window((0, 0));
+
+    f.pointer_to(50.0, 50.0);
+    f.button(BTN_LEFT, ButtonState::Pressed);
+    f.roundtrip(&mut client);
+    client.start_move(idx);
+    f.roundtrip(&mut client);
+
+    f.pointer_to(150.0, 80.0);
+    f.button(BTN_LEFT, ButtonState::Released);
+    f.roundtrip(&mut client);
+
+    assert_eq!(f.window_location(&client, idx), Point::from((100, 30)));
+}
+
+#[test]
+fn resize_request_configures_new_size() {
+    let (mut f, mut client, idx) = Fixture::with_window((0, 0));
+    let first_serial = client.last_configure(idx).serial;
+
+    f.pointer_to(190.0, 90.0);
+    f.button(BTN_LEFT, ButtonState::Pressed);
+    f.roundtrip(&mut client);
+    client.start_resize(idx, xdg_toplevel::ResizeEdge::BottomRight);
+    f.roundtrip(&mut client);
+
+    f.pointer_to(290.0, 140.0);
+    f.roundtrip(&mut client);
+
+    let configure = client.last_configure(idx);
+    assert!(configure.serial > first_serial);
+    assert_eq!(configure.size, (300, 150));
+    assert!(configure.states.contains(&xdg_toplevel::State::Resizing));
+
+    f.button(BTN_LEFT, ButtonState::Released);
+    f.roundtrip(&mut client);
+
+    let configure = client.last_configure(idx);
+    assert_eq!(configure.size, (300, 150));
+    assert!(!configure.states.contains(&xdg_toplevel::State::Resizing));
+    assert_eq!(f.window(&client, idx).geometry().size, (300, 150).into());
+}
+
+const BINDINGS_CONFIG: &str = r#"
+[keybindings.overview]
+combo = "LOGO+TAB"
+action = "toggle_overview"
+
+[keybindings.close]
+combo = "logo+code:24"
+action = "close"
+"#;
+
+#[test]
+fn bindings_consume_their_key() {
+    let mut f = Fixture::with_toml(BINDINGS_CONFIG);
+    let mut client = f.client();
+    let idx = f.open_window(&mut client, (0, 0));
+
+    f.pointer_to(50.0, 50.0);
+    f.click(BTN_LEFT);
+    f.roundtrip(&mut client);
+
+    f.press_combo(&[KEY_LEFTMETA, KEY_Q]);
+    f.press_combo(&[KEY_LEFTMETA, KEY_W]);
+    f.roundtrip(&mut client);
+
+    // The keycode binding matched q, w was not bound and reached the client.
+    assert!(client.state.windows[idx].close_requested);
+    assert_eq!(
+        client.state.pressed_keys,
+        vec![KEY_LEFTMETA, KEY_LEFTMETA, KEY_W]
+    );
+}
+
+#[test]
+fn combo_names_are_case_insensitive() {
+    let mut f = Fixture::with_toml(BINDINGS_CONFIG);
+
+    f.press_combo(&[KEY_LEFTMETA, KEY_TAB]);
+    assert!(f.state.overview.is_some());
+}
+
+const MODES_CONFIG: &str = r#"
+[keybindings.resize]
+combo = "logo+r"
+action = { enter_mode = "resize" }
+
+[modes.resize.grow]
+combo = "right"
+action = "grow_width"
+
+[modes.resize.done]
+combo = "escape"
+action = "exit_mode"
+"#;
+
+#[test]
+fn binding_modes_switch_the_active_bindings() {
+    let mut f = Fixture::with_toml(MODES_CONFIG);
+    let mut client = f.client();
+    let idx = f.open_window(&mut client, (0, 0));
+
+    f.pointer_to(50.0, 50.0);
+    f.click(BTN_LEFT);
+    f.press_combo(&[KEY_LEFTMETA, KEY_R]);
+    assert_eq!(
+        f.ipc(&Request::Mode),
+        Response::Ok(Reply::Mode("resize".into()))
+    );
+
+    f.press_combo(&[KEY_RIGHT]);
+    f.roundtrip(&mut client);
+    assert_eq!(client.last_configure(idx).size, (264, 100));
+
+    f.press_combo(&[KEY_ESC]);
+    f.press_combo(&[KEY_RIGHT]);
+    f.roundtrip(&mut client);
+    assert_eq!(f.state.mode, "default");
+    assert_eq!(client.state.pressed_keys, vec![KEY_LEFTMETA, KEY_RIGHT]);
+}
+
+#[test]
+fn modifier_left_drag_moves_window() {
+    let (mut f, mut client, idx) = Fixture::with_window((0, 0));
+
+    f.pointer_to(50.0, 50.0);
+    f.key(KEY_LEFTMETA, KeyState::Pressed);
+    f.button(BTN_LEFT, ButtonState::Pressed);
+    f.pointer_to(150.0, 80.0);
+    f.button(BTN_LEFT, ButtonState::Released);
+    f.key(KEY_LEFTMETA, KeyState::Released);
+    f.roundtrip(&mut client);
+
+    assert_eq!(f.window_location(&client, idx), Point::from((100, 30)));
+    // The drag belongs to the compositor, the client never sees the click.
+    assert_eq!(client.state.last_button_serial, None);
+    assert!(!f.state.seat.get_pointer().unwrap().is_grabbed());
+}
+
+#[test]
+fn modifier_right_drag_resizes_from_nearest_corner() {
+    let (mut f, mut client, idx) = Fixture::with_window((0, 0));
+
+    f.pointer_to(20.0, 20.0);
+    f.key(KEY_LEFTMETA, KeyState::Pressed);
+    f.button(BTN_RIGHT, ButtonState::Pressed);
+    f.pointer_to(0.0, 0.0);
+    f.roundtrip(&mut client);
+
+    let configure = client.last_configure(idx);
+    assert_eq!(configure.size, (220, 120));
+    assert!(configure.states.contains(&xdg_toplevel::State::Resizing));
+
+    f.button(BTN_RIGHT, ButtonState::Released);
+    f.key(KEY_LEFTMETA, KeyState::Released);
+    f.roundtrip(&mut client);
+
+    // Growing from the top-left corner keeps the bottom-right one in place.
+    assert_eq!(f.window_location(&client, idx), Point::from((-20, -20)));
+}
+
+const SCROLL_CONFIG: &str = r#"
+[keybindings.zoom]
+combo = "logo+scroll_up"
+action = "zoom_in"
+"#;
+
+#[test]
+fn scrolling_reaches_the_client_under_the_pointer() {
+    let mut f = Fixture::with_toml(SCROLL_CONFIG);
+    let mut client = f.client();
+    f.open_window(&mut client, (0, 0));
+
+    f.pointer_to(50.0, 50.0);
+    f.scroll(2.0);
+    f.roundtrip(&mut client);
+
+    assert_eq!(client.state.scrolled, 30.0);
+}
+
+#[test]
+fn modifier_scroll_runs_binding_instead() {
+    let mut f = Fixture::with_toml(SCROLL_CONFIG);
+    let mut client = f.client();
+    f.open_window(&mut client, (0, 0));
+
+    f.pointer_to(50.0, 50.0);
+    f.key(KEY_LEFTMETA, KeyState::Pressed);
+    f.scroll(-1.0);
+    f.key(KEY_LEFTMETA, KeyState::Released);
+    f.roundtrip(&mut client);
+
+    assert_eq!(f.state.viewport(&f.output).zoom, 1.25);
+    assert_eq!(client.state.scrolled, 0.0);
+}
+
+#[test]
+fn modifier_scroll_keeps_its_stop_from_the_client() {
+    let mut f = Fixture::with_toml(SCROLL_CONFIG);
+    let mut client = f.client();
+    f.open_window(&mut client, (0, 0));
+
+    f.pointer_to(50.0, 50.0);
+    f.finger_scroll(-20.0);
+    f.finger_scroll(0.0);
+    f.roundtrip(&mut client);
+    assert_eq!(client.state.scroll_stops, 1);
+
+    f.key(KEY_LEFTMETA, KeyState::Pressed);
+    f.finger_scroll(-20.0);
+    f.finger_scroll(0.0);
+    f.key(KEY_LEFTMETA, KeyState::Released);
+    f.roundtrip(&mut client);
+
+    assert_eq!(f.state.viewport(&f.output).zoom, 1.25);
+    assert_eq!(client.state.scrolled, -20.0);
+    assert_eq!(client.state.scroll_stops, 1);
+}
+
+#[test]
+fn relative_motion_is_clamped_to_the_outputs() {
+    let mut f = Fixture::new();
+    let pointer = f.state.seat.get_pointer().unwrap();
+
+    f.pointer_to(100.0, 100.0);
+    f.pointer_by(50.0, -20.0);
+    assert_eq!(pointer.current_location(), Point::from((150.0, 80.0)));
+
+    f.pointer_by(5000.0, -5000.0);
+    assert_eq!(pointer.current_location(), Point::from((1919.0, 0.0)));
+}
+
+#[test]
+fn relative_motion_reaches_the_client_unclamped() {
+    let (mut f, mut client, _) = Fixture::with_window((0, 0));
+
+    f.pointer_to(50.0, 50.0);
+    f.pointer_by(30.0, -10.0);
+    // Stopped at the top of the output, which relative pointer clients never see.
+    f.pointer_by(0.0, -500.0);
+    f.roundtrip(&mut client);
+
+    assert_eq!(client.state.relative_motions, [(30.0, -10.0), (0.0, -500.0)]);
+}
+
+#[test]
+fn relative_motion_follows_the_zoom() {
+    let mut f = Fixture::new();
+    let pointer = f.state.seat.get_pointer().unwrap();
+
+    f.state.zoom_output(&f.output.clone(), 2.0, Point::from((0.0, 0.0)));
+    f.pointer_to(100.0, 100.0);
+    f.pointer_by(100.0, 0.0);
+
+    // 100 screen pixels cover 50 canvas units at twice the size.
+    assert_eq!(pointer.current_location(), Point::from((100.0, 50.0)));
+}
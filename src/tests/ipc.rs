@@ -0,0 +1,138 @@
+use std::{
+    io::{ErrorKind, Read, Write},
+    time::{Duration, Instant},
+};
+
+use smithay::utils::Point;
+
+use super::fixture::Fixture;
+use crate::ipc::protocol::{Reply, Request, Response};
+
+#[test]
+fn lists_outputs_and_windows() {
+    let (mut f, _client, _) = Fixture::with_window((0, 0));
+
+    let Response::Ok(Reply::Outputs(outputs)) = f.ipc(&Request::Outputs) else {
+        panic!("unexpected reply to outputs");
+    };
+    assert_eq!(outputs.len(), 1);
+    assert_eq!(outputs[0].name, "HEADLESS-1");
+
+    let Response::Ok(Reply::Windows(windows)) = f.ipc(&Request::Windows) else {
+        panic!("unexpected reply to windows");
+    };
+    assert_eq!(windows.len(), 1);
+    assert_eq!((windows[0].width, windows[0].height), (200, 100));
+    assert!(!windows[0].focused);
+}
+
+#[test]
+fn focuses_and_moves_windows() {
+    let (mut f, mut client, idx) = Fixture::with_window((0, 0));
+
+    let Response::Ok(Reply::Windows(windows)) = f.ipc(&Request::Windows) else {
+        panic!("unexpected reply to windows");
+    };
+    let id = windows[0].id;
+
+    assert_eq!(f.ipc(&Request::Focus { id }), Response::Ok(Reply::Handled));
+    assert_eq!(
+        f.ipc(&Request::Move { id, x: 300, y: 40 }),
+        Response::Ok(Reply::Handled)
+    );
+    f.roundtrip(&mut client);
+
+    assert_eq!(
+        client.state.keyboard_focus.as_ref(),
+        Some(&client.state.windows[idx].surface)
+    );
+    assert_eq!(f.window_location(&client, idx), Point::from((300, 40)));
+}
+
+#[test]
+fn rejects_unknown_windows_and_malformed_requests() {
+    let mut f = Fixture::new();
+
+    assert!(matches!(
+        f.ipc(&Request::Close { id: 9999 }),
+        Response::Error(_)
+    ));
+
+    let reply: Response = serde_json::from_str(&f.ipc_raw("{\"request\":\"dance\"}")).unwrap();
+    assert!(matches!(reply, Response::Error(_)));
+}
+
+#[test]
+fn queues_replies_for_clients_that_are_slow_to_read() {
+    let (mut f, _client, _) = Fixture::with_window((0, 0));
+    let mut stream = f.ipc_connect();
+
+    // Enough replies to overflow the socket buffer before the client reads any of them.
+    let count = 4000;
+    let request = format!("{}\n", serde_json::to_string(&Request::Windows).unwrap());
+    stream.write_all(request.repeat(count).as_bytes()).unwrap();
+    stream.set_nonblocking(true).unwrap();
+
+    let mut replies = Vec::new();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while replies.iter().filter(|b| **b == b'\n').count() < count {
+        assert!(Instant::now() < deadline, "the replies never all arrived");
+        f.dispatch();
+
+        let mut buf = [0u8; 4096];
+        while let Ok(n) = stream.read(&mut buf) {
+            assert_ne!(n, 0, "the client was dropped");
+            replies.extend_from_slice(&buf[..n]);
+        }
+    }
+
+    for line in String::from_utf8(replies).unwrap().lines() {
+        let reply: Response = serde_json::from_str(line).unwrap();
+        assert!(matches!(reply, Response::Ok(Reply::Windows(_))));
+    }
+}
+
+#[test]
+fn drops_clients_sending_overlong_requests() {
+    let mut f = Fixture::new();
+    let mut stream = f.ipc_connect();
+
+    stream.write_all(&[b' '; 70 * 1024]).unwrap();
+    stream.set_nonblocking(true).unwrap();
+
+    let mut buf = [0u8; 4096];
+    for _ in 0..64 {
+        f.dispatch();
+        match stream.read(&mut buf) {
+            Ok(0) => return,
+            Err(err) if err.kind() == ErrorKind::ConnectionReset => return,
+            Ok(_) => panic!("unexpected reply to an unterminated request"),
+            Err(_) => {}
+        }
+    }
+    panic!("the client was never dropped");
+}
+
+#[test]
+fn spawned_commands_get_the_socket_path() {
+    let f = Fixture::new();
+    let out = std::env::temp_dir().join(format!("clux-test-{}-socket-env", std::process::id()));
+    let command = format!(
+        "printf %s \"$CLUX_SOCKET\" > {0}.tmp && mv {0}.tmp {0}",
+        out.display()
+    );
+    f.state.spawn(&command).unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let written = loop {
+        if let Ok(written) = std::fs::read_to_string(&out) {
+            break written;
+        }
+        assert!(Instant::now() < deadline, "the command never ran");
+        std::thread::sleep(Duration::from_millis(10));
+    };
+    std::fs::remove_file(&out).unwrap();
+
+    let path = &f.state.ipc.as_ref().unwrap().path;
+    assert_eq!(written, path.to_str().unwrap());
+}
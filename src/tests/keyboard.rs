@@ -0,0 +1,309 @@
+use std::path::{Path, PathBuf};
+
+use smithay::{backend::input::KeyState, utils::SERIAL_COUNTER};
+
+use super::fixture::{Fixture, KEY_LEFTMETA, KEY_R};
+use crate::ipc::protocol::{LayoutInfo, Reply, Request, Response};
+
+const KEY_LEFTSHIFT: u32 = 42;
+const KEY_LEFTALT: u32 = 56;
+const KEY_SPACE: u32 = 57;
+
+const LAYOUTS_CONFIG: &str = r#"
+[keyboard]
+layout = ["us", "de"]
+variant = ["", "nodeadkeys"]
+repeat_delay = 300
+repeat_rate = 40
+
+[keybindings.layout]
+combo = "logo+space"
+action = "cycle_layout"
+"#;
+
+fn active_layout(f: &mut Fixture) -> LayoutInfo {
+    let Response::Ok(Reply::Layout(layout)) = f.ipc(&Request::Layout) else {
+        panic!("unexpected reply to layout");
+    };
+    layout
+}
+
+#[test]
+fn repeat_settings_reach_clients() {
+    let mut f = Fixture::with_toml(LAYOUTS_CONFIG);
+    let mut client = f.client();
+    assert_eq!(client.state.repeat_info, Some((40, 300)));
+
+    let reloaded = LAYOUTS_CONFIG.replace("repeat_rate = 40", "repeat_rate = 0");
+    f.state.apply_config(toml::from_str(&reloaded).unwrap());
+    f.roundtrip(&mut client);
+    assert_eq!(client.state.repeat_info, Some((0, 300)));
+}
+
+#[test]
+fn binding_cycles_through_layouts() {
+    let mut f = Fixture::with_toml(LAYOUTS_CONFIG);
+    assert_eq!(
+        active_layout(&mut f),
+        LayoutInfo {
+            index: 0,
+            name: "English (US)".to_string(),
+        }
+    );
+
+    f.press_combo(&[KEY_LEFTMETA, KEY_SPACE]);
+    let layout = active_layout(&mut f);
+    assert_eq!(layout.index, 1);
+    assert!(layout.name.starts_with("German"));
+
+    f.press_combo(&[KEY_LEFTMETA, KEY_SPACE]);
+    assert_eq!(active_layout(&mut f).index, 0);
+}
+
+#[test]
+fn xkb_option_switches_layouts() {
+    let mut f = Fixture::with_toml(
+        r#"[keyboard]
+layout = "us,de"
+variant = ""
+options = "grp:alt_shift_toggle"
+"#,
+    );
+
+    f.press_combo(&[KEY_LEFTALT, KEY_LEFTSHIFT]);
+    assert_eq!(active_layout(&mut f).index, 1);
+}
+
+const GERMAN_KEYMAP: &str = r#"xkb_keymap {
+    xkb_keycodes { include "evdev+aliases(qwertz)" };
+    xkb_types { include "complete" };
+    xkb_compat { include "complete" };
+    xkb_symbols { include "pc+de+inet(evdev)" };
+};
+"#;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("clux-test-{}-{}", std::process::id(), name))
+}
+
+fn keymap_file_config(keymap_file: &Path) -> String {
+    format!("[keyboard]\nkeymap_file = \"{}\"\n", keymap_file.display())
+}
+
+#[test]
+fn keymap_file_replaces_the_layout() {
+    let keymap_file = temp_path("replaces.xkb");
+    std::fs::write(&keymap_file, GERMAN_KEYMAP).unwrap();
+
+    let mut f = Fixture::with_toml(&keymap_file_config(&keymap_file));
+    std::fs::remove_file(&keymap_file).unwrap();
+
+    assert!(active_layout(&mut f).name.starts_with("German"));
+}
+
+#[test]
+fn broken_keymap_file_keeps_the_previous_keymap() {
+    let keymap_file = temp_path("broken.xkb");
+    let config_file = temp_path("broken.toml");
+    let config = keymap_file_config(&keymap_file);
+    std::fs::write(&keymap_file, GERMAN_KEYMAP).unwrap();
+    std::fs::write(&config_file, &config).unwrap();
+    let mut f = Fixture::with_toml(&config);
+
+    std::fs::write(&keymap_file, "xkb_keymap {").unwrap();
+    let err = f.state.reload_config_from(&config_file).unwrap_err();
+    let message = format!("{err:#}");
+    assert!(message.contains("could not compile keymap file"));
+    assert!(message.contains(&keymap_file.display().to_string()));
+
+    // Applied without the reload's check, the keyboard still keeps its keymap.
+    f.state.apply_config(toml::from_str(&config).unwrap());
+    std::fs::remove_file(&keymap_file).unwrap();
+    std::fs::remove_file(&config_file).unwrap();
+
+    assert!(active_layout(&mut f).name.starts_with("German"));
+}
+
+fn device_config(device: &str) -> String {
+    format!(
+        r#"[keyboard]
+layout = "us"
+
+[[keyboard.devices]]
+name = "{device}"
+layout = "de"
+"#
+    )
+}
+
+#[test]
+fn typing_on_a_keyboard_installs_its_keymap() {
+    let mut f = Fixture::with_toml(&device_config("Clux test device"));
+    let mut client = f.client();
+    let keymaps = client.state.keymaps;
+    assert_eq!(active_layout(&mut f).name, "English (US)");
+
+    f.press_combo(&[KEY_SPACE]);
+    f.roundtrip(&mut client);
+    assert!(active_layout(&mut f).name.starts_with("German"));
+    assert_eq!(client.state.keymaps, keymaps + 1);
+
+    // Further keys from the same keyboard leave the keymap alone.
+    f.press_combo(&[KEY_SPACE]);
+    f.roundtrip(&mut client);
+    assert_eq!(client.state.keymaps, keymaps + 1);
+}
+
+#[test]
+fn modifiers_held_on_one_keyboard_apply_to_another() {
+    let config = device_config("USB Keyboard")
+        + r#"
+[keybindings.resize]
+combo = "logo+r"
+action = { enter_mode = "resize" }
+
+[modes.resize.done]
+combo = "escape"
+action = "exit_mode"
+"#;
+    let mut f = Fixture::with_toml(&config);
+
+    f.key(KEY_LEFTMETA, KeyState::Pressed);
+    f.key_on("USB Keyboard", KEY_R, KeyState::Pressed);
+    f.key_on("USB Keyboard", KEY_R, KeyState::Released);
+    f.key(KEY_LEFTMETA, KeyState::Released);
+
+    assert!(active_layout(&mut f).name.starts_with("German"));
+    assert_eq!(f.state.mode, "resize");
+}
+
+#[test]
+fn cycled_layout_survives_a_keyboard_switch() {
+    let config = LAYOUTS_CONFIG.to_string()
+        + r#"
+[[keyboard.devices]]
+name = "USB Keyboard"
+layout = ["us", "de"]
+variant = ["", "deadacute"]
+"#;
+    let mut f = Fixture::with_toml(&config);
+
+    f.press_combo(&[KEY_LEFTMETA, KEY_SPACE]);
+    f.key_on("USB Keyboard", KEY_SPACE, KeyState::Pressed);
+    f.key_on("USB Keyboard", KEY_SPACE, KeyState::Released);
+
+    let layout = active_layout(&mut f);
+    assert_eq!(layout.index, 1);
+    assert!(layout.name.starts_with("German"));
+}
+
+#[test]
+fn unknown_layout_starts_with_the_default_keymap() {
+    let mut f = Fixture::with_toml("[keyboard]\nlayout = \"nosuchlayout\"\n");
+    assert_eq!(active_layout(&mut f).name, "English (US)");
+}
+
+#[test]
+fn broken_device_entry_keeps_the_other_keymaps() {
+    let config = device_config("Clux test device")
+        + r#"
+[[keyboard.devices]]
+name = "USB Keyboard"
+layout = "nosuchlayout"
+"#;
+    let mut f = Fixture::with_toml(&config);
+
+    f.key_on("USB Keyboard", KEY_SPACE, KeyState::Pressed);
+    f.key_on("USB Keyboard", KEY_SPACE, KeyState::Released);
+    assert_eq!(active_layout(&mut f).name, "English (US)");
+
+    f.press_combo(&[KEY_SPACE]);
+    assert!(active_layout(&mut f).name.starts_with("German"));
+}
+
+#[test]
+fn other_keyboards_keep_the_keyboard_section_keymap() {
+    let mut f = Fixture::with_toml(&device_config("USB Keyboard"));
+
+    f.press_combo(&[KEY_SPACE]);
+    assert_eq!(active_layout(&mut f).name, "English (US)");
+}
+
+#[test]
+fn reload_without_the_device_entry_restores_the_keymap() {
+    let mut f = Fixture::with_toml(&device_config("Clux test device"));
+    f.press_combo(&[KEY_SPACE]);
+    assert!(active_layout(&mut f).name.starts_with("German"));
+
+    f.state
+        .apply_config(toml::from_str("[keyboard]\nlayout = \"us\"\n").unwrap());
+    assert_eq!(active_layout(&mut f).name, "English (US)");
+}
+
+/// Focuses the windows `client` made in turn, cycling the layout once while the first one has the
+/// focus, and returns the layout each focus change ends up with.
+fn layouts_across_windows(config: &str) -> Vec<u32> {
+    let mut f = Fixture::with_toml(config);
+    let mut client = f.client();
+    let first = client.create_window();
+    let second = client.create_window();
+    f.roundtrip(&mut client);
+    let (first, second) = (f.window(&client, first), f.window(&client, second));
+
+    f.state.focus_window(&first, SERIAL_COUNTER.next_serial());
+    f.dispatch();
+    f.press_combo(&[KEY_LEFTMETA, KEY_SPACE]);
+
+    let mut layouts = Vec::new();
+    for window in [&second, &first, &second] {
+        f.state.focus_window(window, SERIAL_COUNTER.next_serial());
+        f.dispatch();
+        layouts.push(active_layout(&mut f).index);
+    }
+    layouts
+}
+
+fn window_tracking_config() -> String {
+    LAYOUTS_CONFIG.replace(
+        "repeat_rate = 40",
+        "repeat_rate = 40\nlayout_tracking = \"window\"",
+    )
+}
+
+#[test]
+fn windows_get_back_their_own_layout() {
+    assert_eq!(layouts_across_windows(&window_tracking_config()), [0, 1, 0]);
+}
+
+#[test]
+fn window_keeps_its_layout_when_the_focus_leaves_for_nothing() {
+    let mut f = Fixture::with_toml(&window_tracking_config());
+    let mut client = f.client();
+    let first = client.create_window();
+    let second = client.create_window();
+    f.roundtrip(&mut client);
+    let (first, second) = (f.window(&client, first), f.window(&client, second));
+    let keyboard = f.state.seat.get_keyboard().unwrap();
+
+    f.state.focus_window(&first, SERIAL_COUNTER.next_serial());
+    f.dispatch();
+    f.press_combo(&[KEY_LEFTMETA, KEY_SPACE]);
+
+    // With nothing focused, this layout belongs to no window.
+    keyboard.set_focus(&mut f.state, None, SERIAL_COUNTER.next_serial());
+    f.dispatch();
+    f.press_combo(&[KEY_LEFTMETA, KEY_SPACE]);
+
+    f.state.focus_window(&second, SERIAL_COUNTER.next_serial());
+    f.dispatch();
+    assert_eq!(active_layout(&mut f).index, 0);
+
+    f.state.focus_window(&first, SERIAL_COUNTER.next_serial());
+    f.dispatch();
+    assert_eq!(active_layout(&mut f).index, 1);
+}
+
+#[test]
+fn global_layout_follows_no_window() {
+    assert_eq!(layouts_across_windows(LAYOUTS_CONFIG), [1, 1, 1]);
+}
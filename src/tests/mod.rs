@@ -0,0 +1,22 @@
+//! In-process integration tests.
+//!
+//! Every test boots a real `Clux` on a virtual output, talks to it with real Wayland clients over
+//! its listening socket, and injects input through `process_input_event`.
+
+mod client;
+mod fixture;
+
+mod actions;
+mod canvas;
+mod check;
+mod cursor;
+mod decorations;
+mod devices;
+mod gestures;
+mod input;
+mod ipc;
+mod keyboard;
+mod reload;
+mod tablet;
+mod touch;
+mod xdg_shell;
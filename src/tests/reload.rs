@@ -0,0 +1,47 @@
+use super::fixture::{Fixture, KEY_LEFTMETA, KEY_TAB};
+
+const OUTPUT_CONFIG: &str = r#"
+[[outputs]]
+name = "HEADLESS-1"
+pos = [0, 0]
+"#;
+
+const MOVED_OUTPUT_CONFIG: &str = r#"
+[keybindings.overview]
+combo = "logo+tab"
+action = "toggle_overview"
+
+[[outputs]]
+name = "HEADLESS-1"
+pos = [500, 0]
+scale = 2.0
+"#;
+
+#[test]
+fn reload_moves_and_scales_outputs() {
+    let mut f = Fixture::with_toml(OUTPUT_CONFIG);
+
+    f.state
+        .apply_config(toml::from_str(MOVED_OUTPUT_CONFIG).unwrap());
+
+    let geometry = f.state.space.output_geometry(&f.output).unwrap();
+    assert_eq!(geometry.loc, (500, 0).into());
+    assert_eq!(geometry.size, (960, 540).into());
+    assert_eq!(f.output.current_scale().fractional_scale(), 2.0);
+}
+
+#[test]
+fn reload_applies_new_bindings_and_keeps_them_on_parse_errors() {
+    let mut f = Fixture::with_toml(OUTPUT_CONFIG);
+    let path = std::env::temp_dir().join(format!("clux-test-config-{}.toml", std::process::id()));
+
+    std::fs::write(&path, MOVED_OUTPUT_CONFIG).unwrap();
+    f.state.reload_config_from(&path).unwrap();
+
+    std::fs::write(&path, "[keyboard\nlayout = ").unwrap();
+    assert!(f.state.reload_config_from(&path).is_err());
+    std::fs::remove_file(&path).unwrap();
+
+    f.press_combo(&[KEY_LEFTMETA, KEY_TAB]);
+    assert!(f.state.overview.is_some());
+}
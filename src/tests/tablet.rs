@@ -0,0 +1,84 @@
+use smithay::{
+    backend::input::{ButtonState, TabletToolTipState},
+    utils::Point,
+};
+
+use super::{
+    client::TabletEvent,
+    fixture::{Fixture, OUTPUT_SIZE},
+};
+
+const BTN_STYLUS: u32 = 0x14b;
+
+#[test]
+fn pen_reaches_the_window_under_it() {
+    let (mut f, mut client, idx) = Fixture::with_window((100, 100));
+
+    f.add_tablet();
+    f.roundtrip(&mut client);
+    assert_eq!(client.state.tablets, 1);
+
+    let (width, height) = (OUTPUT_SIZE.0 as f64, OUTPUT_SIZE.1 as f64);
+    f.tablet_proximity_in(150.0 / width, 120.0 / height);
+    f.tablet_tip(TabletToolTipState::Down);
+    f.tablet_motion(160.0 / width, 140.0 / height);
+    f.tablet_button(BTN_STYLUS, ButtonState::Pressed);
+    f.tablet_tip(TabletToolTipState::Up);
+    f.tablet_proximity_out();
+    f.roundtrip(&mut client);
+
+    let surface = &client.state.windows[idx].surface;
+    assert_eq!(client.state.tablet_tools, 1);
+    assert!(client
+        .state
+        .tablet_events
+        .contains(&TabletEvent::Motion { x: 60.0, y: 40.0 }));
+
+    let events: Vec<_> = client
+        .state
+        .tablet_events
+        .iter()
+        .filter(|event| !matches!(event, TabletEvent::Motion { .. }))
+        .cloned()
+        .collect();
+    assert_eq!(
+        events,
+        vec![
+            TabletEvent::ProximityIn(surface.clone()),
+            TabletEvent::Down,
+            TabletEvent::Button {
+                button: BTN_STYLUS,
+                pressed: true
+            },
+            TabletEvent::Up,
+            TabletEvent::ProximityOut,
+        ]
+    );
+    assert_eq!(client.state.keyboard_focus.as_ref(), Some(surface));
+}
+
+#[test]
+fn pen_stays_within_its_mapped_region() {
+    let mut f = Fixture::with_toml(
+        r#"[[input.devices]]
+type = "tablet"
+region = [100, 50, 400, 300]
+"#,
+    );
+    f.add_tablet();
+
+    f.tablet_proximity_in(0.5, 0.5);
+    assert_eq!(
+        f.state.tablet_tool_location,
+        Some(Point::from((300.0, 200.0)))
+    );
+
+    f.tablet_motion(1.0, 0.0);
+    assert_eq!(
+        f.state.tablet_tool_location,
+        Some(Point::from((500.0, 50.0)))
+    );
+
+    f.tablet_proximity_out();
+    assert_eq!(f.state.tablet_tool_location, None);
+}
@@ -0,0 +1,61 @@
+use smithay::utils::Point;
+
+use super::fixture::{Fixture, OUTPUT_SIZE};
+use crate::backend::headless;
+
+#[test]
+fn touch_reaches_the_window_under_the_finger() {
+    let (mut f, mut client, idx) = Fixture::with_window((100, 100));
+
+    f.touch_down(0, 150.0, 120.0);
+    f.touch_up(0);
+    f.roundtrip(&mut client);
+
+    let surface = &client.state.windows[idx].surface;
+    assert_eq!(
+        client.state.touch_downs,
+        vec![(surface.clone(), (50.0, 20.0))]
+    );
+    assert_eq!(client.state.keyboard_focus.as_ref(), Some(surface));
+}
+
+#[test]
+fn touchscreen_follows_its_mapped_output() {
+    let mut f = Fixture::with_toml(
+        r#"[[input.devices]]
+name = "Clux test device"
+output = "HEADLESS-2"
+"#,
+    );
+    let second = headless::create_output(&mut f.state, "HEADLESS-2", OUTPUT_SIZE);
+    f.state.map_output(&second, (OUTPUT_SIZE.0, 0).into());
+
+    let mut client = f.client();
+    f.open_window(&mut client, (OUTPUT_SIZE.0 + 100, 100));
+
+    f.touch_down(0, 150.0, 120.0);
+    f.touch_up(0);
+    f.roundtrip(&mut client);
+
+    assert_eq!(client.state.touch_downs.len(), 1);
+    assert_eq!(client.state.touch_downs[0].1, (50.0, 20.0));
+}
+
+#[test]
+fn touch_move_request_drags_the_window() {
+    let (mut f, mut client, idx) = Fixture::with_window((0, 0));
+
+    f.touch_down(0, 50.0, 50.0);
+    f.roundtrip(&mut client);
+    client.start_touch_move(idx);
+    f.roundtrip(&mut client);
+
+    f.touch_motion(0, 150.0, 250.0);
+    assert_eq!(f.window_location(&client, idx), Point::from((100, 200)));
+
+    // Lifting the finger ends the move.
+    f.touch_up(0);
+    f.touch_down(0, 400.0, 400.0);
+    f.touch_motion(0, 500.0, 500.0);
+    assert_eq!(f.window_location(&client, idx), Point::from((100, 200)));
+}
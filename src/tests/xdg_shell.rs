@@ -0,0 +1,35 @@
+use smithay::desktop::PopupManager;
+
+use super::{client::DEFAULT_WINDOW_SIZE, fixture::Fixture};
+
+#[test]
+fn toplevel_is_configured_and_mapped() {
+    let mut f = Fixture::new();
+    let mut client = f.client();
+
+    let idx = client.create_window();
+    f.roundtrip(&mut client);
+
+    assert_eq!(f.state.space.elements().count(), 1);
+    assert!(!client.state.windows[idx].configures.is_empty());
+
+    let window = f.window(&client, idx);
+    assert_eq!(window.geometry().size, DEFAULT_WINDOW_SIZE.into());
+}
+
+#[test]
+fn popup_is_configured_and_tracked() {
+    let mut f = Fixture::new();
+    let mut client = f.client();
+
+    let parent = client.create_window();
+    f.roundtrip(&mut client);
+    let popup = client.create_popup(parent, (50, 40));
+    f.roundtrip(&mut client);
+
+    assert!(client.state.popups[popup].configured);
+
+    let window = f.window(&client, parent);
+    let toplevel = window.toplevel().unwrap().wl_surface().clone();
+    assert_eq!(PopupManager::popups_for_surface(&toplevel).count(), 1);
+}
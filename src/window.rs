@@ -0,0 +1,50 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use smithay::{
+    desktop::Window,
+    wayland::{compositor::with_states, shell::xdg::XdgToplevelSurfaceData},
+};
+
+use crate::Clux;
+
+/// Stable identifier of a window, used to refer to it from outside the compositor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WindowId(pub u64);
+
+impl WindowId {
+    fn next() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+        Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+
+    pub fn of(window: &Window) -> Self {
+        window.user_data().insert_if_missing_threadsafe(Self::next);
+        *window.user_data().get::<Self>().unwrap()
+    }
+}
+
+/// Returns the app_id and title the client set on `window`.
+pub fn app_id_and_title(window: &Window) -> (Option<String>, Option<String>) {
+    let Some(toplevel) = window.toplevel() else {
+        return (None, None);
+    };
+
+    with_states(toplevel.wl_surface(), |states| {
+        let data = states
+            .data_map
+            .get::<XdgToplevelSurfaceData>()
+            .unwrap()
+            .lock()
+            .unwrap();
+        (data.app_id.clone(), data.title.clone())
+    })
+}
+
+impl Clux {
+    pub fn window_by_id(&self, id: WindowId) -> Option<Window> {
+        self.space
+            .elements()
+            .find(|w| WindowId::of(w) == id)
+            .cloned()
+    }
+}